@@ -1,4 +1,21 @@
+use crate::sensors::SensorRig;
 
 pub struct Network {
     pub layers: Vec<u32>,
+    pub rig: SensorRig,
+}
+
+impl Network {
+    // the input layer is sized by the sensor rig the network was trained with
+    pub fn new(rig: SensorRig, hidden: &[u32], outputs: u32) -> Network {
+        let mut layers = vec![rig.input_count() as u32];
+        layers.extend_from_slice(hidden);
+        layers.push(outputs);
+
+        Network { layers, rig }
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.rig.input_count()
+    }
 }
@@ -1,41 +1,125 @@
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
 use rand::Rng;
 
 use crate::*;
 use crate::geo::Point;
+use crate::input::Input;
+use crate::player::Player;
+
+pub const TICK_MS: f64 = 1000.0 / 60.0;
+pub const MAX_TICKS_PER_FRAME: u32 = 5;
+
+thread_local! {
+    static CURRENT_ENTITIES: RefCell<Weak<RefCell<Entities>>> = const { RefCell::new(Weak::new()) };
+    static CURRENT_CAMERA: RefCell<Weak<RefCell<Camera>>> = const { RefCell::new(Weak::new()) };
+    static CURRENT_GAME: RefCell<Option<GameLoop>> = const { RefCell::new(None) };
+}
+
+pub fn with_camera<T>(f: impl FnOnce(&mut Camera) -> T) -> Option<T> {
+    let camera = CURRENT_CAMERA.with(|current| current.borrow().upgrade())?;
+    let mut camera = camera.try_borrow_mut().ok()?;
+
+    Some(f(&mut camera))
+}
+
+pub fn set_current(game: Option<GameLoop>) {
+    let previous = CURRENT_GAME.with(|current| current.replace(game));
+    drop(previous);
+}
+
+type AnimationFrame = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+pub struct GameLoop {
+    frame: AnimationFrame,
+    frame_id: Rc<Cell<i32>>,
+}
+
+impl GameLoop {
+    pub fn stop(&mut self) {
+        js::cancel_animation_frame(self.frame_id.get());
+        // the closure owns the game, dropping it removes all of the game's listeners
+        self.frame.borrow_mut().take();
+    }
+}
+
+impl Drop for GameLoop {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+pub fn with_entities<T>(f: impl FnOnce(&mut Entities) -> T) -> Option<T> {
+    let entities = CURRENT_ENTITIES.with(|current| current.borrow().upgrade())?;
+    let mut entities = entities.try_borrow_mut().ok()?;
+
+    Some(f(&mut entities))
+}
 
 pub struct Game {
     pub entities: Rc<RefCell<Entities>>,
     pub network: Network,
     pub game_canvas: Rc<RefCell<Canvas>>,
     pub node_canvas: Rc<RefCell<Canvas>>,
+    pub camera: Rc<RefCell<Camera>>,
+    pub minimap: Minimap,
+    pub hud: Hud,
+    pub input: Input,
+
+    last_frame: Option<f64>,
+    pending: f64,
 }
 
 impl Game {
     pub fn new() -> Game {
+        Game::with_road(Road::load())
+    }
+
+    pub fn with_road(road: Road) -> Game {
         let game_canvas = Rc::new(RefCell::new(Canvas::from("gameCanvas")));
         let node_canvas = Rc::new(RefCell::new(Canvas::from("nodeCanvas")));
 
-        let entities = Rc::new(RefCell::new(Entities::new()));
-        let network = Network { layers: vec![] };
+        let body = Car::default();
+        let network = Network::new(sensors::SensorRig::default().with_body(body.height, body.width), &[6], 4);
+        let entities = Rc::new(RefCell::new(Entities::with_road(road).with_sensor_rig(&network.rig)));
 
         Game {
             entities,
             network,
             game_canvas,
             node_canvas,
+            camera: Rc::new(RefCell::new(Camera::new())),
+            minimap: Minimap::new(),
+            hud: Hud::new(),
+            input: Input::new(),
+
+            last_frame: None,
+            pending: 0.0,
         }
     }
 
-    pub fn update(&self) {
+    pub fn update(&mut self) {
+        self.poll_gamepads();
         self.entities.borrow_mut().update();
+        self.hud.ticks.tick(js::now());
         // self.network.update();
     }
 
-    pub fn draw(&self) {
-        let game_canvas = self.game_canvas.borrow();
-        let node_canvas = self.node_canvas.borrow();
+    pub fn poll_gamepads(&self) {
+        for player in self.entities.borrow().players.iter() {
+            let mut car = player.car.borrow_mut();
+
+            if !car.has_collided {
+                player.gamepad.poll(&mut car.controls);
+            }
+        }
+    }
+
+    pub fn draw(&mut self) {
+        self.hud.frames.tick(js::now());
+
+        let mut game_canvas = self.game_canvas.borrow_mut();
+        let mut node_canvas = self.node_canvas.borrow_mut();
 
         game_canvas.resize();
         node_canvas.resize();
@@ -43,59 +127,131 @@ impl Game {
         game_canvas.clear();
         node_canvas.clear();
 
-        self.entities.borrow().draw(&game_canvas.context);
+        game_canvas.reset_transform();
+        node_canvas.reset_transform();
+
+        let entities = self.entities.borrow();
+        let mut camera = self.camera.borrow_mut();
+        camera.resize(game_canvas.width, game_canvas.height);
+        camera.update(&entities);
+
+        game_canvas.context.save();
+        camera.apply(&game_canvas.context, game_canvas.pixel_ratio);
+        entities.draw(&game_canvas.context);
+        game_canvas.context.restore();
+
+        self.minimap.draw(&game_canvas.context, &entities, &camera);
+        self.hud.draw(&game_canvas.context, &entities);
     }
 
-    pub fn run(&self) {
-        self.update();
+    pub fn run(&mut self) {
+        let now = js::now();
+        let elapsed = self.last_frame.map_or(TICK_MS, |last| now - last);
+        self.last_frame = Some(now);
+
+        // the simulation runs in fixed steps whatever the display rate, a slow frame catches up a few at most
+        self.pending = (self.pending + elapsed).min(TICK_MS * MAX_TICKS_PER_FRAME as f64);
+        while self.pending >= TICK_MS {
+            self.update();
+            self.pending -= TICK_MS;
+        }
+
         self.draw();
     }
 
-    pub fn start(self) {
-        let animation_callback = Rc::new(RefCell::new(None));
-        let callback_initializer = animation_callback.clone();
+    pub fn add_mouse_listeners(&mut self) {
+        let element = self.game_canvas.borrow().element.clone();
+        let drag_origin = Rc::new(Cell::new(None::<Point>));
 
         {
             let context = self.game_canvas.clone();
             let entities = self.entities.clone();
+            let camera = self.camera.clone();
+            let drag_origin = drag_origin.clone();
             let mut rng = rand::thread_rng();
 
-            let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
-                let point = Point::from_event(&event, &context.borrow().element);
+            self.input.listen(&element, "mousedown", move |event: web_sys::MouseEvent| {
+                let screen_point = Point::from_event(&event, &context.borrow().element);
+
+                // left click edits the road, any other button drags the camera
+                if event.button() != 0 {
+                    drag_origin.set(Some(screen_point));
+                    return;
+                }
+
+                let point = camera.borrow().screen_to_world(&screen_point);
                 let entities_borrow = &mut entities.borrow_mut();
 
                 entities_borrow.road.add_point(point, rng.gen_range(35.0..75.00));
-                entities_borrow.road.construct();
-                entities_borrow.finish_line = FinishLine::new(&entities_borrow.road);
+                entities_borrow.rebuild_track();
+                js::get_element_by_id("rustOutput").set_text_content(Some(&entities_borrow.road.describe()));
 
                 console_log!("{} {}", event.client_x(), event.client_y());
             });
+        }
+
+        {
+            let context = self.game_canvas.clone();
+            let camera = self.camera.clone();
+            let drag_origin = drag_origin.clone();
 
-            let _ = self.game_canvas.borrow().element.add_event_listener_with_callback("mousedown", closure.as_ref().unchecked_ref());
-            // let f = || self.game_canvas.borrow().element.remove_event_listener_with_callback("mousedown", closure.as_ref().unchecked_ref());
+            self.input.listen_window("mousemove", move |event: web_sys::MouseEvent| {
+                let Some(origin) = drag_origin.get() else { return };
+                let point = Point::from_event(&event, &context.borrow().element);
 
-            closure.forget();
+                camera.borrow_mut().pan(point.x - origin.x, point.y - origin.y);
+                drag_origin.set(Some(point));
+            });
         }
-        //
-        // let cars = Rc::new(RefCell::new(Vec::new()));
-        // for _ in 0..10000 {
-        //     cars.borrow_mut().push(Car::default())
-        // }
-        //
-        // let game_canvas = self.game_canvas.clone();
-        // let node_canvas = self.game_canvas.clone();
-
-        let mut i = 0;
-        *callback_initializer.borrow_mut() = Some(Closure::new(move || {
-            self.run();
 
-            i += 1;
-            let text = format!("requestAnimationFrame has been called {} times.", i);
-            // js::get_element_by_id("rustOutput").set_text_content(Some(&text));
+        self.input.listen_window("mouseup", move |_: web_sys::MouseEvent| {
+            drag_origin.set(None);
+        });
+
+        self.input.listen(&element, "contextmenu", |event: web_sys::MouseEvent| {
+            event.prevent_default();
+        });
+
+        {
+            let context = self.game_canvas.clone();
+            let camera = self.camera.clone();
+
+            self.input.listen(&element, "wheel", move |event: web_sys::WheelEvent| {
+                event.prevent_default();
+
+                let point = Point::from_event(&event, &context.borrow().element);
+                let factor = if event.delta_y() < 0.0 { 1.1 } else { 1.0 / 1.1 };
+
+                camera.borrow_mut().zoom_at(&point, factor);
+            });
+        }
+    }
+
+    pub fn start(mut self) -> GameLoop {
+        CURRENT_ENTITIES.with(|current| *current.borrow_mut() = Rc::downgrade(&self.entities));
+
+        CURRENT_CAMERA.with(|current| *current.borrow_mut() = Rc::downgrade(&self.camera));
+        js::get_element_by_id("rustOutput").set_text_content(Some(&self.entities.borrow().road.describe()));
+
+        self.add_mouse_listeners();
+        self.entities.borrow_mut().players.iter_mut().for_each(Player::add_controls_listeners);
+
+        let frame: AnimationFrame = Rc::new(RefCell::new(None));
+        let frame_id = Rc::new(Cell::new(0));
 
-            js::request_animation_frame(animation_callback.borrow().as_ref().unwrap());
+        let animation_callback = Rc::downgrade(&frame);
+        let animation_frame_id = frame_id.clone();
+
+        *frame.borrow_mut() = Some(Closure::new(move || {
+            self.run();
+
+            if let Some(callback) = animation_callback.upgrade() {
+                animation_frame_id.set(js::request_animation_frame(callback.borrow().as_ref().unwrap()));
+            }
         }));
 
-        js::request_animation_frame(callback_initializer.borrow().as_ref().unwrap());
+        frame_id.set(js::request_animation_frame(frame.borrow().as_ref().unwrap()));
+
+        GameLoop { frame, frame_id }
     }
 }
@@ -10,6 +10,7 @@ pub use visual::*;
 pub use utils::*;
 
 use game::Game;
+use bindings::ControlAction;
 
 use wasm_bindgen::prelude::*;
 
@@ -29,7 +30,122 @@ fn run() -> Result<(), JsValue> {
     panic_utils::set_panic_hook();
 
     let game = Game::new();
-    game.start();
+    game::set_current(Some(game.start()));
 
     Ok(()) 
 }
+
+#[wasm_bindgen]
+pub fn restart() {
+    game::set_current(None);
+    game::set_current(Some(Game::new().start()));
+}
+
+#[wasm_bindgen]
+pub fn load_track(points: &[f64]) {
+    let mut road = Road::new();
+    points.chunks_exact(3).for_each(|p| road.add_point(geo::Point::new(p[0], p[1]), p[2]));
+    road.construct();
+
+    game::set_current(None);
+    game::set_current(Some(Game::with_road(road).start()));
+}
+
+#[wasm_bindgen]
+pub fn set_key_binding(player: usize, action: &str, key: &str) -> bool {
+    let Some(action) = ControlAction::from_name(action) else { return false };
+
+    game::with_entities(|entities| match entities.players.get(player) {
+        Some(player) => { player.bindings.borrow_mut().rebind(action, key); true }
+        None => false,
+    }).unwrap_or(false)
+}
+
+#[wasm_bindgen]
+pub fn add_key_binding(player: usize, action: &str, key: &str) -> bool {
+    let Some(action) = ControlAction::from_name(action) else { return false };
+
+    game::with_entities(|entities| match entities.players.get(player) {
+        Some(player) => { player.bindings.borrow_mut().bind(key, action); true }
+        None => false,
+    }).unwrap_or(false)
+}
+
+#[wasm_bindgen]
+pub fn unbind_key(player: usize, key: &str) -> bool {
+    game::with_entities(|entities| match entities.players.get(player) {
+        Some(player) => { player.bindings.borrow_mut().unbind_key(key); true }
+        None => false,
+    }).unwrap_or(false)
+}
+
+#[wasm_bindgen]
+pub fn set_camera_mode(mode: &str, index: usize) -> bool {
+    let Some(mode) = CameraMode::from_name(mode, index) else { return false };
+
+    game::with_camera(|camera| camera.mode = mode).is_some()
+}
+
+#[wasm_bindgen]
+pub fn export_svg() -> Option<String> {
+    game::with_entities(|entities| entities.to_svg())
+}
+
+#[wasm_bindgen]
+pub fn set_show_trails(show: bool) {
+    game::with_entities(|entities| entities.show_trails = show);
+}
+
+#[wasm_bindgen]
+pub fn set_show_heatmap(show: bool) {
+    game::with_entities(|entities| entities.show_heatmap = show);
+}
+
+#[wasm_bindgen]
+pub fn next_generation() {
+    game::with_entities(|entities| entities.next_generation());
+}
+
+#[wasm_bindgen]
+pub fn set_sensor_noise(std_dev: f64, quantization: f64, dropout: f64, seed: u64) {
+    let noise = sensors::SensorNoise::new(std_dev, quantization, dropout, seed);
+    game::with_entities(|entities| entities.set_sensor_noise(Some(noise)));
+}
+
+#[wasm_bindgen]
+pub fn clear_sensor_noise() {
+    game::with_entities(|entities| entities.set_sensor_noise(None));
+}
+
+#[wasm_bindgen]
+pub fn set_collision_response(mode: &str, amount: f64) -> bool {
+    let Some(response) = CollisionResponse::from_name(mode, amount) else { return false };
+
+    game::with_entities(|entities| entities.set_collision_response(response)).is_some()
+}
+
+#[wasm_bindgen]
+pub fn set_player_collision_response(player: usize, mode: &str, amount: f64) -> bool {
+    let Some(response) = CollisionResponse::from_name(mode, amount) else { return false };
+
+    game::with_entities(|entities| match entities.players.get(player) {
+        Some(player) => { player.car.borrow_mut().response = response; true }
+        None => false,
+    }).unwrap_or(false)
+}
+
+#[wasm_bindgen]
+pub fn spawn_fleet(count: usize) -> bool {
+    game::with_entities(|entities| entities.spawn_fleet(count)).is_some()
+}
+
+// one network input vector per car, each as long as the network's input layer
+#[wasm_bindgen]
+pub fn fleet_readings() -> Option<Vec<f64>> {
+    game::with_entities(|entities| entities.fleet.readings.clone())
+}
+
+#[wasm_bindgen]
+pub fn set_fleet_controls(controls: &[f64]) -> bool {
+    game::with_entities(|entities| entities.fleet.set_controls(controls)).is_some()
+}
@@ -1,6 +1,4 @@
-use wasm_bindgen::JsValue;
-
-use crate::{helpers::lerpf, Drawable};
+use crate::{helpers::{clampf, lerpf}, Drawable, Renderer};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Point {
@@ -28,6 +26,16 @@ impl Point {
         self.y = center.y + x * sin + y * cos;
     }
 
+    pub fn transform(&self, x: f64, y: f64, angle: f64) -> Point {
+        let cos = angle.cos();
+        let sin = angle.sin();
+
+        Point {
+            x: x + self.x * cos - self.y * sin,
+            y: y + self.x * sin + self.y * cos,
+        }
+    }
+
     pub fn angle(&self, other: &Point) -> f64 {
         (other.y - self.y).atan2(other.x - self.x)
     }
@@ -50,6 +58,21 @@ impl Point {
         self.move_towards_point(other, -distance)
     }
 
+    pub fn bounds(points: &[Point]) -> Option<(Point, Point)> {
+        let first = points.first()?;
+        let mut min = *first;
+        let mut max = *first;
+
+        for point in points.iter().skip(1) {
+            min.x = min.x.min(point.x);
+            min.y = min.y.min(point.y);
+            max.x = max.x.max(point.x);
+            max.y = max.y.max(point.y);
+        }
+
+        Some((min, max))
+    }
+
     pub fn from_event(event: &web_sys::MouseEvent, element: &web_sys::Element) -> Point {
         let rect = element.get_bounding_client_rect();
         let x = event.client_x() as f64 - rect.left();
@@ -91,6 +114,23 @@ impl Line {
         self.start.angle(&self.end)
     }
 
+    pub fn point_at(&self, t: f64) -> Point {
+        Point::new(lerpf(self.start.x, self.end.x, t), lerpf(self.start.y, self.end.y, t))
+    }
+
+    pub fn project(&self, point: &Point) -> f64 {
+        let d_x = self.end.x - self.start.x;
+        let d_y = self.end.y - self.start.y;
+        let length_squared = d_x * d_x + d_y * d_y;
+
+        if length_squared == 0.0 {
+            return 0.0;
+        }
+
+        let t = ((point.x - self.start.x) * d_x + (point.y - self.start.y) * d_y) / length_squared;
+        clampf(t, 0.0, 1.0)
+    }
+
     pub fn translate(&mut self, x: f64, y: f64) {
         self.start.x += x;
         self.start.y += y;
@@ -157,15 +197,19 @@ impl Line {
         );
     }
 
+    // each segment's ends have to lie on opposite sides of the other one, or on it
     pub fn intersects_lines(a: &Point, b: &Point, c: &Point, d: &Point) -> bool {
-        let d_y = b.y - a.y;
-        let d_x = a.x - b.x;
-        let m = d_y * a.x + d_x * a.y;
+        let side = |p: &Point, q: &Point, r: &Point| (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x);
 
-        let u = c.x * d_y + c.y * d_x - m;
-        let v = d.x * d_y + d.y * d_x - m;
+        let (u, v) = (side(a, b, c), side(a, b, d));
+        let (s, t) = (side(c, d, a), side(c, d, b));
 
-        u * v <= 0.0
+        // collinear segments only touch where their extents overlap
+        if u == 0.0 && v == 0.0 {
+            return Line::check_aabb_intersection(a, b, c, d);
+        }
+
+        u * v <= 0.0 && s * t <= 0.0
     }
 
     pub fn get_intersection(&self, other: &Line) -> Option<Intersection> {
@@ -206,7 +250,7 @@ impl Line {
 }
 
 impl Drawable for Line {
-     fn draw(&self, context: &web_sys::CanvasRenderingContext2d) {
+     fn draw(&self, context: &dyn Renderer) {
         context.begin_path();
         context.move_to(self.start.x, self.start.y);
         context.line_to(self.end.x, self.end.y);
@@ -214,6 +258,7 @@ impl Drawable for Line {
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct Polygon {
     pub points: Vec<Point>,
     pub fill_color: String,
@@ -260,6 +305,58 @@ impl Polygon {
         }
     }
 
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        Point::bounds(&self.points)
+    }
+
+    // even-odd rule, so self-overlapping outlines like the road's count the area between boundaries
+    pub fn contains_point(&self, point: &Point) -> bool {
+        let mut inside = false;
+        let count = self.points.len();
+
+        for i in 0..count {
+            let a = &self.points[i];
+            let b = &self.points[(i + 1) % count];
+
+            if (a.y > point.y) != (b.y > point.y) {
+                let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if point.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+
+        inside
+    }
+
+    pub fn contains_polygon(&self, other: &Polygon) -> bool {
+        !other.points.is_empty()
+            && other.points.iter().all(|p| self.contains_point(p))
+            && !self.intersects(other)
+    }
+
+    // separating axis test, only exact for convex polygons
+    pub fn overlaps_convex(&self, other: &Polygon) -> bool {
+        if self.points.is_empty() || other.points.is_empty() {
+            return false;
+        }
+
+        for polygon in [self, other] {
+            for edge in polygon.lines() {
+                let axis = Point::new(edge.start.y - edge.end.y, edge.end.x - edge.start.x);
+
+                let (min_a, max_a) = polygon_projection(self, &axis);
+                let (min_b, max_b) = polygon_projection(other, &axis);
+
+                if max_a < min_b || max_b < min_a {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
     pub fn rectangle(x: f64, y: f64, width: f64, height: f64, angle: f64) -> Polygon {
         let half_width = width / 2.0;
         let half_height = height / 2.0;
@@ -333,6 +430,11 @@ impl Polygon {
         }
     }
 
+    pub fn transform_from(&mut self, local: &Polygon, x: f64, y: f64, angle: f64) {
+        self.points.clear();
+        self.points.extend(local.points.iter().map(|p| p.transform(x, y, angle)));
+    }
+
     pub fn translate(&mut self, x: f64, y: f64) {
         for point in self.points.iter_mut() {
             point.x += x;
@@ -363,14 +465,20 @@ impl Polygon {
     }
 }
 
+fn polygon_projection(polygon: &Polygon, axis: &Point) -> (f64, f64) {
+    polygon.points.iter()
+        .map(|p| p.x * axis.x + p.y * axis.y)
+        .fold((f64::MAX, f64::MIN), |(min, max), d| (min.min(d), max.max(d)))
+}
+
 impl Drawable for Polygon {
-    fn draw(&self, context: &web_sys::CanvasRenderingContext2d) {
+    fn draw(&self, context: &dyn Renderer) {
         if self.points.len() < 2 {
             return;
         }
 
-        context.set_fill_style(&JsValue::from_str(self.fill_color.as_str()));
-        context.set_stroke_style(&JsValue::from_str(self.stroke_color.as_str()));
+        context.set_fill_color(self.fill_color.as_str());
+        context.set_stroke_color(self.stroke_color.as_str());
         context.begin_path();
 
         context.move_to(self.points[0].x, self.points[0].y);
@@ -383,3 +491,487 @@ impl Drawable for Polygon {
         context.stroke();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::f64::consts::PI;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6 * (1.0 + a.abs().max(b.abs()))
+    }
+
+    fn approx_point(a: &Point, b: &Point) -> bool {
+        approx(a.x, b.x) && approx(a.y, b.y)
+    }
+
+    fn line(a: (f64, f64), b: (f64, f64)) -> Line {
+        Line::new(Point::new(a.0, a.1), Point::new(b.0, b.1))
+    }
+
+    fn square(x: f64, y: f64, size: f64) -> Polygon {
+        Polygon::rectangle(x, y, size, size, 0.0)
+    }
+
+    #[test]
+    fn point_distance() {
+        assert_eq!(Point::new(0.0, 0.0).distance(&Point::new(3.0, 4.0)), 5.0);
+        assert_eq!(Point::new(1.0, 1.0).distance(&Point::new(1.0, 1.0)), 0.0);
+    }
+
+    #[test]
+    fn point_angle() {
+        let origin = Point::default();
+
+        assert!(approx(origin.angle(&Point::new(1.0, 0.0)), 0.0));
+        assert!(approx(origin.angle(&Point::new(0.0, 1.0)), PI / 2.0));
+        assert!(approx(origin.angle(&Point::new(-1.0, 0.0)), PI));
+    }
+
+    #[test]
+    fn point_rotate_around() {
+        let mut point = Point::new(2.0, 1.0);
+        point.rotate_around(PI / 2.0, &Point::new(1.0, 1.0));
+
+        assert!(approx_point(&point, &Point::new(1.0, 2.0)));
+    }
+
+    #[test]
+    fn point_transform_rotates_then_translates() {
+        let point = Point::new(1.0, 0.0).transform(10.0, 20.0, PI / 2.0);
+
+        assert!(approx_point(&point, &Point::new(10.0, 21.0)));
+    }
+
+    #[test]
+    fn point_move_towards_and_away() {
+        let mut point = Point::default();
+        point.move_towards(0.0, 5.0);
+        assert!(approx_point(&point, &Point::new(5.0, 0.0)));
+
+        point.move_away(0.0, 5.0);
+        assert!(approx_point(&point, &Point::default()));
+
+        let mut point = Point::default();
+        point.move_towards_point(&Point::new(0.0, 10.0), 4.0);
+        assert!(approx_point(&point, &Point::new(0.0, 4.0)));
+    }
+
+    #[test]
+    fn point_bounds() {
+        assert!(Point::bounds(&[]).is_none());
+
+        let (min, max) = Point::bounds(&[Point::new(1.0, 5.0), Point::new(-2.0, 3.0), Point::new(4.0, -1.0)]).unwrap();
+        assert_eq!(min, Point::new(-2.0, -1.0));
+        assert_eq!(max, Point::new(4.0, 5.0));
+    }
+
+    #[test]
+    fn line_length_and_angle() {
+        let line = line((0.0, 0.0), (0.0, -2.0));
+
+        assert_eq!(line.length(), 2.0);
+        assert!(approx(line.angle(), -PI / 2.0));
+    }
+
+    #[test]
+    fn line_point_at_and_project() {
+        let line = line((0.0, 0.0), (10.0, 0.0));
+
+        assert_eq!(line.point_at(0.25), Point::new(2.5, 0.0));
+        assert!(approx(line.project(&Point::new(4.0, 7.0)), 0.4));
+        assert_eq!(line.project(&Point::new(-5.0, 1.0)), 0.0);
+        assert_eq!(line.project(&Point::new(15.0, 1.0)), 1.0);
+    }
+
+    #[test]
+    fn degenerate_line_projects_to_start() {
+        let line = line((3.0, 3.0), (3.0, 3.0));
+
+        assert_eq!(line.project(&Point::new(10.0, 10.0)), 0.0);
+    }
+
+    #[test]
+    fn line_move_separate_offsets_each_end() {
+        let mut line = line((0.0, 0.0), (10.0, 0.0));
+        line.move_towards_separate(PI / 2.0, 1.0, 3.0);
+
+        assert!(approx_point(&line.start, &Point::new(0.0, 1.0)));
+        assert!(approx_point(&line.end, &Point::new(10.0, 3.0)));
+    }
+
+    #[test]
+    fn crossing_segments_intersect() {
+        let a = line((0.0, 0.0), (10.0, 10.0));
+        let b = line((0.0, 10.0), (10.0, 0.0));
+
+        let intersection = a.get_intersection(&b).unwrap();
+        assert!(intersection.intersects);
+        assert!(approx(intersection.offset, 0.5));
+        assert!(approx_point(&intersection.point, &Point::new(5.0, 5.0)));
+    }
+
+    #[test]
+    fn offset_is_measured_along_the_first_line() {
+        let a = line((0.0, 0.0), (10.0, 0.0));
+        let b = line((2.0, -1.0), (2.0, 1.0));
+
+        assert!(approx(a.get_intersection(&b).unwrap().offset, 0.2));
+        assert!(approx(b.get_intersection(&a).unwrap().offset, 0.5));
+    }
+
+    #[test]
+    fn segments_touching_at_an_endpoint_intersect() {
+        let a = line((0.0, 0.0), (5.0, 0.0));
+        let b = line((5.0, 0.0), (5.0, 5.0));
+
+        let intersection = a.get_intersection(&b).unwrap();
+        assert!(intersection.intersects);
+        assert!(approx(intersection.offset, 1.0));
+    }
+
+    #[test]
+    fn disjoint_segments_report_the_extended_intersection() {
+        let a = line((0.0, 0.0), (1.0, 0.0));
+        let b = line((5.0, -1.0), (5.0, 1.0));
+
+        let intersection = a.get_intersection(&b).unwrap();
+        assert!(!intersection.intersects);
+        assert!(approx(intersection.offset, 5.0));
+    }
+
+    #[test]
+    fn parallel_and_collinear_segments_have_no_intersection() {
+        let a = line((0.0, 0.0), (10.0, 0.0));
+
+        assert!(a.get_intersection(&line((0.0, 1.0), (10.0, 1.0))).is_none());
+        assert!(a.get_intersection(&line((5.0, 0.0), (15.0, 0.0))).is_none());
+        assert!(a.get_intersection(&line((20.0, 0.0), (30.0, 0.0))).is_none());
+    }
+
+    #[test]
+    fn intersects_lines_checks_both_segments() {
+        let a = Point::new(0.0, 0.0);
+        let b = Point::new(1.0, 0.0);
+
+        assert!(Line::intersects_lines(&a, &b, &Point::new(0.5, -1.0), &Point::new(0.5, 1.0)));
+        assert!(Line::intersects_lines(&a, &b, &Point::new(1.0, 0.0), &Point::new(1.0, 1.0)));
+        assert!(!Line::intersects_lines(&a, &b, &Point::new(50.0, -1.0), &Point::new(50.0, 1.0)));
+        assert!(!Line::intersects_lines(&a, &b, &Point::new(5.0, 0.0), &Point::new(5.0, 1.0)));
+        assert!(!Line::intersects_lines(&a, &b, &Point::new(0.5, 1.0), &Point::new(0.5, 2.0)));
+    }
+
+    #[test]
+    fn intersects_lines_handles_collinear_segments() {
+        let a = Point::new(0.0, 0.0);
+        let b = Point::new(1.0, 0.0);
+
+        assert!(Line::intersects_lines(&a, &b, &Point::new(0.5, 0.0), &Point::new(2.0, 0.0)));
+        assert!(!Line::intersects_lines(&a, &b, &Point::new(2.0, 0.0), &Point::new(3.0, 0.0)));
+    }
+
+    #[test]
+    fn aabb_intersection() {
+        let a = Point::new(0.0, 0.0);
+        let b = Point::new(2.0, 2.0);
+
+        assert!(Line::check_aabb_intersection(&a, &b, &Point::new(1.0, 1.0), &Point::new(3.0, 3.0)));
+        assert!(Line::check_aabb_intersection(&a, &b, &Point::new(2.0, 2.0), &Point::new(3.0, 3.0)));
+        assert!(!Line::check_aabb_intersection(&a, &b, &Point::new(2.5, 0.0), &Point::new(3.0, 3.0)));
+    }
+
+    #[test]
+    fn matching_points() {
+        let a = line((0.0, 0.0), (1.0, 0.0));
+
+        assert!(a.matches_both_points(&line((0.0, 0.0), (1.0, 0.0))));
+        assert!(a.matches_both_points(&line((1.0, 0.0), (0.0, 0.0))));
+        assert!(!a.matches_both_points(&line((0.0, 0.0), (2.0, 0.0))));
+
+        assert!(a.matches_one_point_only(&line((0.0, 0.0), (0.0, 1.0))));
+        assert!(a.matches_one_point_only(&line((1.0, 0.0), (2.0, 0.0))));
+        assert!(a.matches_one_point_only(&line((5.0, 5.0), (0.0, 0.0))));
+        assert!(!a.matches_one_point_only(&line((0.0, 0.0), (1.0, 0.0))));
+        assert!(!a.matches_one_point_only(&line((1.0, 0.0), (0.0, 0.0))));
+        assert!(!a.matches_one_point_only(&line((5.0, 5.0), (6.0, 6.0))));
+    }
+
+    #[test]
+    fn polygon_lines_close_the_outline() {
+        let lines = square(0.0, 0.0, 2.0).lines();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3].end, lines[0].start);
+        assert!(Polygon::new(vec![], String::new()).lines().is_empty());
+    }
+
+    #[test]
+    fn polygon_center_and_bounds() {
+        let polygon = square(3.0, 4.0, 2.0);
+
+        assert_eq!(polygon.center(), Point::new(3.0, 4.0));
+        assert_eq!(polygon.bounds(), Some((Point::new(2.0, 3.0), Point::new(4.0, 5.0))));
+        assert_eq!(Polygon::new(vec![], String::new()).center(), Point::default());
+    }
+
+    #[test]
+    fn rotated_rectangle_keeps_its_center() {
+        let polygon = Polygon::rectangle(5.0, 5.0, 4.0, 2.0, PI / 2.0);
+
+        assert!(approx_point(&polygon.center(), &Point::new(5.0, 5.0)));
+        let (min, max) = polygon.bounds().unwrap();
+        assert!(approx(max.x - min.x, 2.0));
+        assert!(approx(max.y - min.y, 4.0));
+    }
+
+    #[test]
+    fn overlapping_polygons_intersect() {
+        assert!(square(0.0, 0.0, 2.0).intersects(&square(1.0, 1.0, 2.0)));
+        assert!(!square(0.0, 0.0, 2.0).intersects(&square(5.0, 0.0, 2.0)));
+    }
+
+    #[test]
+    fn contains_point_uses_the_even_odd_rule() {
+        let polygon = square(0.0, 0.0, 4.0);
+
+        assert!(polygon.contains_point(&Point::new(0.0, 0.0)));
+        assert!(polygon.contains_point(&Point::new(1.9, -1.9)));
+        assert!(!polygon.contains_point(&Point::new(3.0, 0.0)));
+        assert!(!Polygon::new(vec![], String::new()).contains_point(&Point::default()));
+
+        // an outer ring walked forwards and an inner ring walked backwards, like the road outline
+        let ring = Polygon::new(vec![
+            Point::new(-4.0, -4.0), Point::new(4.0, -4.0), Point::new(4.0, 4.0), Point::new(-4.0, 4.0),
+            Point::new(-4.0, -4.0), Point::new(-2.0, -2.0),
+            Point::new(-2.0, 2.0), Point::new(2.0, 2.0), Point::new(2.0, -2.0), Point::new(-2.0, -2.0),
+        ], String::new());
+
+        assert!(ring.contains_point(&Point::new(3.0, 0.0)));
+        assert!(!ring.contains_point(&Point::new(0.0, 0.0)));
+        assert!(!ring.contains_point(&Point::new(5.0, 0.0)));
+    }
+
+    #[test]
+    fn nested_polygons_are_contained_without_crossing_edges() {
+        let outer = square(0.0, 0.0, 10.0);
+        let inner = square(1.0, 1.0, 2.0);
+
+        assert!(!outer.intersects(&inner));
+        assert!(outer.contains_polygon(&inner));
+        assert!(!inner.contains_polygon(&outer));
+        assert!(!outer.contains_polygon(&square(4.0, 0.0, 4.0)));
+        assert!(!outer.contains_polygon(&square(20.0, 0.0, 2.0)));
+    }
+
+    #[test]
+    fn separating_axis_overlap() {
+        let a = square(0.0, 0.0, 2.0);
+
+        assert!(a.overlaps_convex(&square(1.5, 0.0, 2.0)));
+        assert!(a.overlaps_convex(&square(0.0, 0.0, 0.5)));
+        assert!(!a.overlaps_convex(&square(3.0, 0.0, 2.0)));
+
+        // close along both axes of the first square but separated along the diamond's edge normals
+        let diamond = Polygon::rectangle(1.6, 1.6, 1.0, 1.0, PI / 4.0);
+        assert!(!a.overlaps_convex(&diamond));
+        assert!(a.overlaps_convex(&Polygon::rectangle(1.2, 1.2, 1.0, 1.0, PI / 4.0)));
+    }
+
+    #[test]
+    fn polygon_scale_and_translate() {
+        let mut polygon = square(1.0, 1.0, 2.0);
+        polygon.scale(2.0, 1.0);
+        assert_eq!(polygon.bounds(), Some((Point::new(-1.0, 0.0), Point::new(3.0, 2.0))));
+
+        polygon.translate(1.0, -1.0);
+        assert_eq!(polygon.center(), Point::new(2.0, 0.0));
+
+        polygon.scale_origin(0.5, 0.5);
+        assert_eq!(polygon.center(), Point::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn transform_from_places_local_points() {
+        let local = square(0.0, 0.0, 2.0);
+        let mut world = Polygon::new(vec![], String::new());
+        world.transform_from(&local, 10.0, 0.0, PI);
+
+        assert_eq!(world.points.len(), 4);
+        assert!(approx_point(&world.center(), &Point::new(10.0, 0.0)));
+        assert!(approx_point(&world.points[0], &Point::new(11.0, 1.0)));
+    }
+
+    fn coordinate() -> impl Strategy<Value = f64> {
+        -1000.0..1000.0
+    }
+
+    fn point() -> impl Strategy<Value = Point> {
+        (coordinate(), coordinate()).prop_map(|(x, y)| Point::new(x, y))
+    }
+
+    proptest! {
+        #[test]
+        fn rotation_preserves_distances(a in point(), b in point(), center in point(), angle in -10.0..10.0f64) {
+            let distance = a.distance(&b);
+            let (mut a, mut b) = (a, b);
+            a.rotate_around(angle, &center);
+            b.rotate_around(angle, &center);
+
+            prop_assert!(approx(a.distance(&b), distance));
+        }
+
+        #[test]
+        fn rotation_preserves_distance_to_center(a in point(), center in point(), angle in -10.0..10.0f64) {
+            let distance = a.distance(&center);
+            let mut a = a;
+            a.rotate_around(angle, &center);
+
+            prop_assert!(approx(a.distance(&center), distance));
+        }
+
+        #[test]
+        fn transform_preserves_distances(a in point(), b in point(), offset in point(), angle in -10.0..10.0f64) {
+            let moved_a = a.transform(offset.x, offset.y, angle);
+            let moved_b = b.transform(offset.x, offset.y, angle);
+
+            prop_assert!(approx(moved_a.distance(&moved_b), a.distance(&b)));
+        }
+
+        #[test]
+        fn intersection_is_translation_invariant(
+            a in point(), b in point(), c in point(), d in point(), offset in point()
+        ) {
+            let first = Line::new(a, b);
+            let second = Line::new(c, d);
+
+            let mut moved_first = first;
+            let mut moved_second = second;
+            moved_first.translate(offset.x, offset.y);
+            moved_second.translate(offset.x, offset.y);
+
+            let before = first.get_intersection(&second);
+            let after = moved_first.get_intersection(&moved_second);
+
+            if let (Some(before), Some(after)) = (before, after) {
+                // skip nearly parallel lines where the result is numerically unstable
+                prop_assume!(before.offset.abs() < 1e3);
+
+                prop_assert!(approx(before.offset, after.offset));
+                prop_assert!(approx(before.point.x + offset.x, after.point.x));
+                prop_assert!(approx(before.point.y + offset.y, after.point.y));
+            }
+        }
+
+        #[test]
+        fn intersection_is_symmetric(a in point(), b in point(), c in point(), d in point()) {
+            let first = Line::new(a, b);
+            let second = Line::new(c, d);
+
+            match (first.get_intersection(&second), second.get_intersection(&first)) {
+                (Some(forward), Some(backward)) => {
+                    prop_assume!(forward.offset.abs() < 1e3 && backward.offset.abs() < 1e3);
+                    // endpoint hits can flip either way through rounding
+                    prop_assume!(forward.offset.abs() > EPSILON && (forward.offset - 1.0).abs() > EPSILON);
+                    prop_assume!(backward.offset.abs() > EPSILON && (backward.offset - 1.0).abs() > EPSILON);
+
+                    prop_assert_eq!(forward.intersects, backward.intersects);
+                    prop_assert!(approx(forward.point.x, backward.point.x));
+                    prop_assert!(approx(forward.point.y, backward.point.y));
+                }
+                (None, None) => {}
+                _ => prop_assert!(false, "only one direction found an intersection"),
+            }
+        }
+
+        #[test]
+        fn intersects_agrees_with_get_intersection(a in point(), b in point(), c in point(), d in point()) {
+            let first = Line::new(a, b);
+            let second = Line::new(c, d);
+
+            if let Some(intersection) = first.get_intersection(&second) {
+                let crossing = second.get_intersection(&first).unwrap();
+                // endpoint hits can flip either way through rounding
+                prop_assume!(intersection.offset.abs() > EPSILON && (intersection.offset - 1.0).abs() > EPSILON);
+                prop_assume!(crossing.offset.abs() > EPSILON && (crossing.offset - 1.0).abs() > EPSILON);
+
+                prop_assert_eq!(first.intersects(&second), intersection.intersects);
+            }
+        }
+
+        #[test]
+        fn intersection_point_lies_on_both_lines(a in point(), b in point(), c in point(), d in point()) {
+            let first = Line::new(a, b);
+            let second = Line::new(c, d);
+
+            if let Some(intersection) = first.get_intersection(&second) {
+                prop_assume!(intersection.intersects);
+
+                let on_second = second.point_at(second.project(&intersection.point));
+                prop_assert!(on_second.distance(&intersection.point) < 1e-6 * (1.0 + second.length()));
+            }
+        }
+
+        #[test]
+        fn parallel_lines_never_intersect(a in point(), b in point(), offset in point()) {
+            prop_assume!(a.distance(&b) > EPSILON);
+
+            let first = Line::new(a, b);
+            let mut second = first;
+            second.translate(offset.x, offset.y);
+
+            // translated copies are parallel, or collinear when the offset runs along the line
+            prop_assert!(first.get_intersection(&second).is_none_or(|i| !i.intersects || offset.distance(&Point::default()) < 1e-6));
+        }
+
+        #[test]
+        fn polygon_rotation_preserves_edge_lengths(center in point(), size in 1.0..100.0f64, angle in -10.0..10.0f64) {
+            let polygon = square(center.x, center.y, size);
+            let mut rotated = square(center.x, center.y, size);
+            rotated.rotate(angle);
+
+            for (before, after) in polygon.lines().iter().zip(rotated.lines().iter()) {
+                prop_assert!(approx(before.length(), after.length()));
+            }
+            prop_assert!(approx_point(&rotated.center(), &polygon.center()));
+        }
+
+        #[test]
+        fn separating_axis_agrees_with_edges_and_containment(
+            a in point(), b in point(), size_a in 1.0..200.0f64, size_b in 1.0..200.0f64, angle in -10.0..10.0f64
+        ) {
+            let first = Polygon::rectangle(a.x, a.y, size_a, size_a * 0.5, 0.0);
+            let second = Polygon::rectangle(b.x, b.y, size_b, size_b * 0.5, angle);
+
+            let touching = first.intersects(&second)
+                || first.contains_point(&second.points[0])
+                || second.contains_point(&first.points[0]);
+
+            prop_assert_eq!(first.overlaps_convex(&second), touching);
+        }
+
+        #[test]
+        fn polygon_translation_moves_the_center(center in point(), offset in point()) {
+            let mut polygon = square(center.x, center.y, 10.0);
+            polygon.translate(offset.x, offset.y);
+
+            prop_assert!(approx_point(&polygon.center(), &Point::new(center.x + offset.x, center.y + offset.y)));
+        }
+
+        #[test]
+        fn projection_stays_on_the_segment(a in point(), b in point(), p in point()) {
+            let line = Line::new(a, b);
+            let t = line.project(&p);
+
+            prop_assert!((0.0..=1.0).contains(&t));
+
+            // no other point on the segment is closer
+            let closest = line.point_at(t).distance(&p);
+            for i in 0..=10 {
+                prop_assert!(closest <= line.point_at(i as f64 / 10.0).distance(&p) + 1e-6);
+            }
+        }
+    }
+}
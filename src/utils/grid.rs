@@ -0,0 +1,234 @@
+use crate::geo::{Line, Point, Polygon};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepHit {
+    // fraction of the motion travelled before the impact, in [0, 1]
+    pub time: f64,
+    pub point: Point,
+    pub line: usize,
+}
+
+pub struct SpatialGrid {
+    pub lines: Vec<Line>,
+    pub cells: Vec<Vec<usize>>,
+    pub origin: Point,
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl SpatialGrid {
+    pub fn new(lines: Vec<Line>, cell_size: f64) -> SpatialGrid {
+        let mut grid = SpatialGrid {
+            lines: vec![],
+            cells: vec![],
+            origin: Point::default(),
+            cell_size,
+            columns: 0,
+            rows: 0,
+        };
+
+        grid.build(lines);
+        grid
+    }
+
+    pub fn build(&mut self, lines: Vec<Line>) {
+        self.cells.clear();
+        self.columns = 0;
+        self.rows = 0;
+
+        if lines.is_empty() {
+            self.lines = lines;
+            return;
+        }
+
+        let mut min = Point::new(f64::MAX, f64::MAX);
+        let mut max = Point::new(f64::MIN, f64::MIN);
+
+        for line in lines.iter() {
+            for point in [line.start, line.end] {
+                min.x = min.x.min(point.x);
+                min.y = min.y.min(point.y);
+                max.x = max.x.max(point.x);
+                max.y = max.y.max(point.y);
+            }
+        }
+
+        self.origin = min;
+        self.columns = ((max.x - min.x) / self.cell_size).floor() as usize + 1;
+        self.rows = ((max.y - min.y) / self.cell_size).floor() as usize + 1;
+        self.cells = vec![vec![]; self.columns * self.rows];
+
+        for (index, line) in lines.iter().enumerate() {
+            let (start_column, start_row, end_column, end_row) = self.cell_range(&line.start, &line.end);
+
+            for row in start_row..=end_row {
+                for column in start_column..=end_column {
+                    self.cells[row * self.columns + column].push(index);
+                }
+            }
+        }
+
+        self.lines = lines;
+    }
+
+    fn cell_range(&self, a: &Point, b: &Point) -> (usize, usize, usize, usize) {
+        let column = |x: f64| {
+            let column = ((x - self.origin.x) / self.cell_size).floor();
+            column.max(0.0).min((self.columns - 1) as f64) as usize
+        };
+        let row = |y: f64| {
+            let row = ((y - self.origin.y) / self.cell_size).floor();
+            row.max(0.0).min((self.rows - 1) as f64) as usize
+        };
+
+        (column(a.x.min(b.x)), row(a.y.min(b.y)), column(a.x.max(b.x)), row(a.y.max(b.y)))
+    }
+
+    fn overlaps_bounds(&self, a: &Point, b: &Point) -> bool {
+        let max_x = self.origin.x + self.columns as f64 * self.cell_size;
+        let max_y = self.origin.y + self.rows as f64 * self.cell_size;
+
+        !(a.x.max(b.x) < self.origin.x || a.y.max(b.y) < self.origin.y ||
+          a.x.min(b.x) > max_x || a.y.min(b.y) > max_y)
+    }
+
+    /// Returns indices of all lines stored in cells overlapped by the bounding box of `a` and `b`
+    pub fn query(&self, a: &Point, b: &Point) -> Vec<usize> {
+        let mut indices = Vec::new();
+        self.query_into(a, b, &mut indices);
+        indices
+    }
+
+    pub fn query_into(&self, a: &Point, b: &Point, indices: &mut Vec<usize>) {
+        indices.clear();
+
+        if self.cells.is_empty() || !self.overlaps_bounds(a, b) {
+            return;
+        }
+
+        let (start_column, start_row, end_column, end_row) = self.cell_range(a, b);
+
+        for row in start_row..=end_row {
+            for column in start_column..=end_column {
+                indices.extend_from_slice(&self.cells[row * self.columns + column]);
+            }
+        }
+
+        indices.sort_unstable();
+        indices.dedup();
+    }
+
+    pub fn query_line(&self, line: &Line) -> Vec<usize> {
+        self.query(&line.start, &line.end)
+    }
+
+    pub fn query_polygon(&self, polygon: &Polygon) -> Vec<usize> {
+        match polygon.bounds() {
+            Some((min, max)) => self.query(&min, &max),
+            None => vec![],
+        }
+    }
+
+    pub fn intersects_line(&self, line: &Line) -> bool {
+        self.query_line(line).into_iter().any(|i| {
+            matches!(line.get_intersection(&self.lines[i]), Some(intersection) if intersection.intersects)
+        })
+    }
+
+    pub fn intersects_polygon(&self, polygon: &Polygon) -> bool {
+        self.intersects_outline(&polygon.points, &mut Vec::new())
+    }
+
+    pub fn intersects_outline(&self, points: &[Point], candidates: &mut Vec<usize>) -> bool {
+        self.intersecting_line(points, candidates).is_some()
+    }
+
+    /// Returns the index of the first grid line crossed by the closed outline through `points`
+    pub fn intersecting_line(&self, points: &[Point], candidates: &mut Vec<usize>) -> Option<usize> {
+        let (min, max) = Point::bounds(points)?;
+
+        self.query_into(&min, &max, candidates);
+        self.crossed_line(points, candidates)
+    }
+
+    /// Like `intersecting_line`, but only tests lines already gathered in `candidates`
+    pub fn crossed_line(&self, points: &[Point], candidates: &[usize]) -> Option<usize> {
+        if candidates.is_empty() {
+            return None;
+        }
+
+        (0..points.len()).find_map(|i| {
+            let edge = Line::new(points[i], points[(i + 1) % points.len()]);
+
+            candidates.iter().copied().find(|&index| {
+                matches!(edge.get_intersection(&self.lines[index]), Some(intersection) if intersection.intersects)
+            })
+        })
+    }
+
+    /// Moves every point of an outline from `from` to `to` and returns the earliest crossing of a grid line
+    pub fn sweep_outline(&self, from: &[Point], to: &[Point], candidates: &mut Vec<usize>) -> Option<SweepHit> {
+        let (from_min, from_max) = Point::bounds(from)?;
+        let (to_min, to_max) = Point::bounds(to)?;
+
+        self.query_into(
+            &Point::new(from_min.x.min(to_min.x), from_min.y.min(to_min.y)),
+            &Point::new(from_max.x.max(to_max.x), from_max.y.max(to_max.y)),
+            candidates,
+        );
+
+        let mut earliest: Option<SweepHit> = None;
+        for (start, end) in from.iter().zip(to.iter()) {
+            if start == end { continue }
+
+            let path = Line::new(*start, *end);
+            for &index in candidates.iter() {
+                let Some(intersection) = path.get_intersection(&self.lines[index]) else { continue };
+                if !intersection.intersects { continue }
+
+                if earliest.is_none_or(|hit| intersection.offset < hit.time) {
+                    earliest = Some(SweepHit { time: intersection.offset, point: intersection.point, line: index });
+                }
+            }
+        }
+
+        earliest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wall() -> SpatialGrid {
+        SpatialGrid::new(vec![Line::new(Point::new(10.0, -50.0), Point::new(10.0, 50.0))], 20.0)
+    }
+
+    fn square_at(x: f64) -> Vec<Point> {
+        vec![Point::new(x - 1.0, -1.0), Point::new(x + 1.0, -1.0), Point::new(x + 1.0, 1.0), Point::new(x - 1.0, 1.0)]
+    }
+
+    #[test]
+    fn sweep_catches_a_jump_over_a_wall() {
+        let grid = wall();
+        let (from, to) = (square_at(0.0), square_at(20.0));
+
+        // both end poses are clear of the wall
+        assert!(!grid.intersects_outline(&from, &mut vec![]));
+        assert!(!grid.intersects_outline(&to, &mut vec![]));
+
+        let hit = grid.sweep_outline(&from, &to, &mut vec![]).unwrap();
+        assert!((hit.time - 0.45).abs() < 1e-9);
+        assert_eq!(hit.line, 0);
+        assert_eq!(hit.point.x, 10.0);
+    }
+
+    #[test]
+    fn sweep_misses_when_motion_stays_clear() {
+        let grid = wall();
+
+        assert!(grid.sweep_outline(&square_at(0.0), &square_at(5.0), &mut vec![]).is_none());
+        assert!(grid.sweep_outline(&square_at(0.0), &square_at(0.0), &mut vec![]).is_none());
+    }
+}
@@ -11,3 +11,8 @@ pub fn clampf(value: f64, min: f64, max: f64) -> f64 {
         value
     }
 }
+
+// wraps an angle in radians into [-PI, PI)
+pub fn wrap_angle(angle: f64) -> f64 {
+    (angle + std::f64::consts::PI).rem_euclid(2.0 * std::f64::consts::PI) - std::f64::consts::PI
+}
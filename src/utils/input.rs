@@ -0,0 +1,69 @@
+use wasm_bindgen::prelude::*;
+use web_sys::EventTarget;
+
+pub struct EventListener {
+    pub target: EventTarget,
+    pub event: String,
+    closure: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl EventListener {
+    pub fn new<E, F>(target: &EventTarget, event: &str, mut callback: F) -> EventListener
+    where
+        E: JsCast,
+        F: FnMut(E) + 'static,
+    {
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::Event| {
+            callback(event.unchecked_into::<E>());
+        });
+
+        let _ = target.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref());
+
+        EventListener {
+            target: target.clone(),
+            event: event.to_owned(),
+            closure,
+        }
+    }
+
+    pub fn remove(&self) {
+        let _ = self.target.remove_event_listener_with_callback(&self.event, self.closure.as_ref().unchecked_ref());
+    }
+}
+
+impl Drop for EventListener {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+#[derive(Default)]
+pub struct Input {
+    pub listeners: Vec<EventListener>,
+}
+
+impl Input {
+    pub fn new() -> Input {
+        Input { listeners: vec![] }
+    }
+
+    pub fn listen<E, F>(&mut self, target: &EventTarget, event: &str, callback: F)
+    where
+        E: JsCast,
+        F: FnMut(E) + 'static,
+    {
+        self.listeners.push(EventListener::new(target, event, callback));
+    }
+
+    pub fn listen_window<E, F>(&mut self, event: &str, callback: F)
+    where
+        E: JsCast,
+        F: FnMut(E) + 'static,
+    {
+        self.listen(&crate::js::window(), event, callback);
+    }
+
+    pub fn clear(&mut self) {
+        self.listeners.clear();
+    }
+}
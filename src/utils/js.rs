@@ -4,10 +4,21 @@ pub fn window() -> web_sys::Window {
     web_sys::window().expect("no global `window` exists")
 }
 
-pub fn request_animation_frame(f: &Closure<dyn FnMut()>) {
+pub fn request_animation_frame(f: &Closure<dyn FnMut()>) -> i32 {
     window()
         .request_animation_frame(f.as_ref().unchecked_ref())
-        .expect("should register `requestAnimationFrame` OK");
+        .expect("should register `requestAnimationFrame` OK")
+}
+
+pub fn cancel_animation_frame(id: i32) {
+    let _ = window().cancel_animation_frame(id);
+}
+
+pub fn now() -> f64 {
+    window()
+        .performance()
+        .expect("should have a performance object on window")
+        .now()
 }
 
 pub fn document() -> web_sys::Document {
@@ -41,3 +52,15 @@ pub fn context_2d(canvas: &web_sys::HtmlCanvasElement) -> web_sys::CanvasRenderi
         .dyn_into::<web_sys::CanvasRenderingContext2d>()
         .unwrap()
 }
+
+// `None` outside a browser as well, e.g. when running under Node
+pub fn gamepad(index: u32) -> Option<web_sys::Gamepad> {
+    web_sys::window()?
+        .navigator()
+        .get_gamepads()
+        .ok()?
+        .get(index)
+        .dyn_into::<web_sys::Gamepad>()
+        .ok()
+        .filter(|gamepad| gamepad.connected())
+}
@@ -3,3 +3,5 @@ pub mod panic_utils;
 pub mod helpers;
 pub mod geo;
 pub mod sprite;
+pub mod grid;
+pub mod input;
@@ -1,5 +1,7 @@
 use crate::geo::{Polygon, Point};
 
+pub const CAR_BODY: usize = 4;
+
 pub fn get_car_sprite() -> Vec<Polygon> {
     let mut polygons = Vec::with_capacity(1);
 
@@ -0,0 +1,125 @@
+use crate::{geo::Point, helpers::{clampf, lerpf}, Entities, Renderer};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraMode {
+    Free,
+    FollowBest,
+    FollowPlayer(usize),
+}
+
+impl CameraMode {
+    pub fn from_name(name: &str, index: usize) -> Option<CameraMode> {
+        match name.to_lowercase().as_str() {
+            "free" => Some(CameraMode::Free),
+            "best" => Some(CameraMode::FollowBest),
+            "player" => Some(CameraMode::FollowPlayer(index)),
+            _ => None,
+        }
+    }
+}
+
+pub struct Camera {
+    pub x: f64,
+    pub y: f64,
+    pub zoom: f64,
+    pub min_zoom: f64,
+    pub max_zoom: f64,
+    pub smoothing: f64,
+    pub mode: CameraMode,
+
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Camera {
+    pub fn new() -> Camera {
+        Camera {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+            min_zoom: 0.1,
+            max_zoom: 10.0,
+            smoothing: 0.1,
+            mode: CameraMode::Free,
+
+            width: 0.0,
+            height: 0.0,
+        }
+    }
+
+    pub fn resize(&mut self, width: f64, height: f64) {
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn screen_to_world(&self, point: &Point) -> Point {
+        Point::new(point.x / self.zoom + self.x, point.y / self.zoom + self.y)
+    }
+
+    pub fn world_to_screen(&self, point: &Point) -> Point {
+        Point::new((point.x - self.x) * self.zoom, (point.y - self.y) * self.zoom)
+    }
+
+    pub fn center(&self) -> Point {
+        self.screen_to_world(&Point::new(self.width / 2.0, self.height / 2.0))
+    }
+
+    pub fn look_at(&mut self, target: &Point) {
+        self.x = target.x - self.width / (2.0 * self.zoom);
+        self.y = target.y - self.height / (2.0 * self.zoom);
+    }
+
+    pub fn follow(&mut self, target: &Point) {
+        let center = self.center();
+        let x = lerpf(center.x, target.x, self.smoothing);
+        let y = lerpf(center.y, target.y, self.smoothing);
+
+        self.look_at(&Point::new(x, y));
+    }
+
+    pub fn zoom_at(&mut self, screen: &Point, factor: f64) {
+        let anchor = self.screen_to_world(screen);
+
+        self.zoom = clampf(self.zoom * factor, self.min_zoom, self.max_zoom);
+        self.x = anchor.x - screen.x / self.zoom;
+        self.y = anchor.y - screen.y / self.zoom;
+    }
+
+    pub fn pan(&mut self, d_x: f64, d_y: f64) {
+        self.mode = CameraMode::Free;
+        self.x -= d_x / self.zoom;
+        self.y -= d_y / self.zoom;
+    }
+
+    pub fn target(&self, entities: &Entities) -> Option<Point> {
+        match self.mode {
+            CameraMode::Free => None,
+            CameraMode::FollowBest => {
+                let index = entities.best_car()?;
+                let car = &entities.cars.borrow()[index];
+                Some(Point::new(car.x, car.y))
+            }
+            CameraMode::FollowPlayer(index) => {
+                let car = entities.players.get(index)?.car.borrow();
+                Some(Point::new(car.x, car.y))
+            }
+        }
+    }
+
+    pub fn update(&mut self, entities: &Entities) {
+        if let Some(target) = self.target(entities) {
+            self.follow(&target);
+        }
+    }
+
+    pub fn apply(&self, context: &dyn Renderer, pixel_ratio: f64) {
+        let scale = self.zoom * pixel_ratio;
+        context.set_transform(scale, 0.0, 0.0, scale, -self.x * scale, -self.y * scale);
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Camera {
+        Camera::new()
+    }
+}
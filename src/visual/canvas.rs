@@ -6,6 +6,9 @@ pub struct Canvas {
     pub id: String,
     pub element: HtmlCanvasElement,
     pub context: CanvasRenderingContext2d,
+    pub width: f64,
+    pub height: f64,
+    pub pixel_ratio: f64,
 }
 
 impl Canvas {
@@ -17,18 +20,41 @@ impl Canvas {
             id: element_id.to_string(),
             element,
             context,
+            width: 0.0,
+            height: 0.0,
+            pixel_ratio: 1.0,
         }
     }
 
-    pub fn resize(&self) {
+    pub fn resize(&mut self) -> bool {
         let computed_style = self.element.get_bounding_client_rect();
+        let pixel_ratio = js::window().device_pixel_ratio().max(1.0);
 
-        self.element.set_width(computed_style.width() as u32);
-        self.element.set_height(computed_style.height() as u32);
+        if computed_style.width() == self.width && computed_style.height() == self.height && pixel_ratio == self.pixel_ratio {
+            return false;
+        }
+
+        self.width = computed_style.width();
+        self.height = computed_style.height();
+        self.pixel_ratio = pixel_ratio;
+
+        // resizing the backing store resets the context, so the scale has to be set again
+        self.element.set_width((self.width * pixel_ratio).round() as u32);
+        self.element.set_height((self.height * pixel_ratio).round() as u32);
+        self.reset_transform();
+
+        true
+    }
+
+    pub fn reset_transform(&self) {
+        let _ = self.context.set_transform(self.pixel_ratio, 0.0, 0.0, self.pixel_ratio, 0.0, 0.0);
     }
 
     pub fn clear(&self) {
-        self.context.clear_rect(0.0, 0.0, self.element.width().into(), self.element.height().into())
+        self.context.save();
+        let _ = self.context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+        self.context.clear_rect(0.0, 0.0, self.element.width().into(), self.element.height().into());
+        self.context.restore();
     }
 
     pub fn draw(&self, drawables: &[impl Drawable]) {
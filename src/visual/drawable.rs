@@ -1,17 +1,17 @@
-use web_sys::CanvasRenderingContext2d;
+use crate::Renderer;
 
 pub trait Drawable {
-    fn draw(&self, context: &CanvasRenderingContext2d);
+    fn draw(&self, context: &dyn Renderer);
 }
 
 impl<T: Drawable> Drawable for Vec<T> {
-    fn draw(&self, context: &CanvasRenderingContext2d) {
+    fn draw(&self, context: &dyn Renderer) {
         self.iter().for_each(|c| c.draw(context));
     }
 }
 
 impl<T: Drawable> Drawable for &[T] {
-    fn draw(&self, context: &CanvasRenderingContext2d) {
+    fn draw(&self, context: &dyn Renderer) {
         self.iter().for_each(|c| c.draw(context));
     }
 }
@@ -0,0 +1,126 @@
+use crate::{geo::Point, Entities, Renderer};
+
+pub struct RateCounter {
+    pub rate: f64,
+    count: u32,
+    window_start: Option<f64>,
+}
+
+impl RateCounter {
+    pub fn new() -> RateCounter {
+        RateCounter {
+            rate: 0.0,
+            count: 0,
+            window_start: None,
+        }
+    }
+
+    // timestamps are in milliseconds, the rate is averaged over one second windows
+    pub fn tick(&mut self, now: f64) {
+        let start = *self.window_start.get_or_insert(now);
+        self.count += 1;
+
+        let elapsed = now - start;
+        if elapsed >= 1000.0 {
+            self.rate = self.count as f64 * 1000.0 / elapsed;
+            self.count = 0;
+            self.window_start = Some(now);
+        }
+    }
+}
+
+impl Default for RateCounter {
+    fn default() -> RateCounter {
+        RateCounter::new()
+    }
+}
+
+pub struct Hud {
+    pub visible: bool,
+    pub margin: f64,
+    pub padding: f64,
+    pub width: f64,
+    pub line_height: f64,
+    pub font: String,
+
+    pub frames: RateCounter,
+    pub ticks: RateCounter,
+}
+
+impl Hud {
+    pub fn new() -> Hud {
+        Hud {
+            visible: true,
+            margin: 10.0,
+            padding: 8.0,
+            width: 220.0,
+            line_height: 16.0,
+            font: "12px monospace".to_owned(),
+
+            frames: RateCounter::new(),
+            ticks: RateCounter::new(),
+        }
+    }
+
+    pub fn lines(&self, entities: &Entities) -> Vec<String> {
+        let cars = entities.cars.borrow();
+        let collided = cars.iter().filter(|c| c.has_collided).count() + entities.fleet.len() - entities.fleet.alive();
+        let total = cars.len() + entities.fleet.len();
+
+        let mut lines = vec![
+            format!("generation  {}", entities.generation),
+            format!("alive       {} / {}", total - collided, total),
+            format!("collided    {}", collided),
+        ];
+
+        match entities.best_car().map(|index| &cars[index]) {
+            Some(car) => {
+                let length = entities.road.length().max(1.0);
+                let progress = entities.road.progress(&Point::new(car.x, car.y));
+
+                lines.push(format!("best        {:.1}%", 100.0 * progress / length));
+                lines.push(format!("speed       {:.2}", car.speed));
+                lines.push(format!("angle       {:.1}°", car.angle.to_degrees().rem_euclid(360.0)));
+                lines.push(format!(
+                    "controls    s{:+.2} t{:.2} b{:.2}",
+                    car.controls.steering_input(),
+                    car.controls.throttle_input(),
+                    car.controls.brake_input(),
+                ));
+            }
+            None => lines.push("best        -".to_owned()),
+        }
+
+        lines.push(format!("fps         {:.0}", self.frames.rate));
+        lines.push(format!("tps         {:.0}", self.ticks.rate));
+
+        lines
+    }
+
+    pub fn draw(&self, context: &dyn Renderer, entities: &Entities) {
+        if !self.visible { return }
+
+        let lines = self.lines(entities);
+        let height = self.padding * 2.0 + self.line_height * lines.len() as f64;
+
+        context.save();
+
+        context.set_fill_color("rgba(0, 0, 0, 0.6)");
+        context.fill_rect(self.margin, self.margin, self.width, height);
+
+        context.set_font(&self.font);
+        context.set_fill_color("white");
+        for (i, line) in lines.iter().enumerate() {
+            let y = self.margin + self.padding + self.line_height * (i as f64 + 0.75);
+            context.fill_text(line, self.margin + self.padding, y);
+        }
+
+        context.restore();
+    }
+}
+
+impl Default for Hud {
+    fn default() -> Hud {
+        Hud::new()
+    }
+}
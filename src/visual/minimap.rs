@@ -0,0 +1,133 @@
+use crate::{geo::{Point, Polygon}, Camera, Entities, Renderer};
+
+pub struct Minimap {
+    pub size: f64,
+    pub margin: f64,
+    pub padding: f64,
+    pub dot_radius: f64,
+}
+
+struct MinimapTransform {
+    origin: Point,
+    offset: Point,
+    scale: f64,
+}
+
+impl MinimapTransform {
+    fn apply(&self, point: &Point) -> Point {
+        Point::new(
+            self.offset.x + (point.x - self.origin.x) * self.scale,
+            self.offset.y + (point.y - self.origin.y) * self.scale,
+        )
+    }
+}
+
+impl Minimap {
+    pub fn new() -> Minimap {
+        Minimap {
+            size: 200.0,
+            margin: 10.0,
+            padding: 8.0,
+            dot_radius: 2.5,
+        }
+    }
+
+    pub fn draw(&self, context: &dyn Renderer, entities: &Entities, camera: &Camera) {
+        let Some((min, max)) = entities.road.hitbox.bounds() else { return };
+
+        let left = camera.width - self.size - self.margin;
+        let top = self.margin;
+        let inner = self.size - self.padding * 2.0;
+        let scale = inner / (max.x - min.x).max(max.y - min.y).max(1.0);
+
+        let transform = MinimapTransform {
+            origin: min,
+            offset: Point::new(
+                left + self.padding + (inner - (max.x - min.x) * scale) / 2.0,
+                top + self.padding + (inner - (max.y - min.y) * scale) / 2.0,
+            ),
+            scale,
+        };
+
+        context.save();
+        context.set_line_width(1.0);
+
+        context.set_fill_color("rgba(0, 0, 0, 0.6)");
+        context.fill_rect(left, top, self.size, self.size);
+
+        self.draw_polygon(context, &entities.road.hitbox, &transform, "white");
+        self.draw_polygon(context, &entities.finish_line.start, &transform, "gold");
+        self.draw_polygon(context, &entities.finish_line.end, &transform, "lime");
+
+        for i in 0..entities.fleet.len() {
+            let color = if entities.fleet.collided[i] { "gray" } else { "blue" };
+            self.draw_dot(context, &Point::new(entities.fleet.xs[i], entities.fleet.ys[i]), &transform, color, 0.6);
+        }
+
+        let best = entities.best_car();
+        for (i, car) in entities.cars.borrow().iter().enumerate() {
+            if Some(i) == best { continue }
+
+            let color = if car.has_collided { "gray" } else { "blue" };
+            self.draw_dot(context, &Point::new(car.x, car.y), &transform, color, 1.0);
+        }
+
+        for player in entities.players.iter() {
+            let car = player.car.borrow();
+            self.draw_dot(context, &Point::new(car.x, car.y), &transform, "orange", 1.0);
+        }
+
+        if let Some(index) = best {
+            let car = &entities.cars.borrow()[index];
+            self.draw_dot(context, &Point::new(car.x, car.y), &transform, "red", 1.8);
+        }
+
+        let view_start = transform.apply(&camera.screen_to_world(&Point::default()));
+        let view_end = transform.apply(&camera.screen_to_world(&Point::new(camera.width, camera.height)));
+
+        context.begin_path();
+        context.rect(left, top, self.size, self.size);
+        context.clip();
+
+        context.set_stroke_color("yellow");
+        context.stroke_rect(view_start.x, view_start.y, view_end.x - view_start.x, view_end.y - view_start.y);
+
+        context.restore();
+    }
+
+    fn draw_polygon(&self, context: &dyn Renderer, polygon: &Polygon, transform: &MinimapTransform, color: &str) {
+        if polygon.points.len() < 2 {
+            return;
+        }
+
+        context.set_fill_color(color);
+        context.set_stroke_color("black");
+        context.begin_path();
+
+        let first = transform.apply(&polygon.points[0]);
+        context.move_to(first.x, first.y);
+        for point in polygon.points.iter().skip(1) {
+            let point = transform.apply(point);
+            context.line_to(point.x, point.y);
+        }
+
+        context.close_path();
+        context.fill();
+        context.stroke();
+    }
+
+    fn draw_dot(&self, context: &dyn Renderer, point: &Point, transform: &MinimapTransform, color: &str, scale: f64) {
+        let point = transform.apply(point);
+
+        context.set_fill_color(color);
+        context.begin_path();
+        context.arc(point.x, point.y, self.dot_radius * scale, 0.0, 2.0 * std::f64::consts::PI);
+        context.fill();
+    }
+}
+
+impl Default for Minimap {
+    fn default() -> Minimap {
+        Minimap::new()
+    }
+}
@@ -1,7 +1,17 @@
 pub mod drawable;
+pub mod renderer;
+pub mod svg;
 pub mod updatable;
 pub mod canvas;
+pub mod camera;
+pub mod minimap;
+pub mod hud;
 
 pub use drawable::*;
+pub use renderer::*;
+pub use svg::*;
 pub use updatable::*;
 pub use canvas::*;
+pub use camera::*;
+pub use minimap::*;
+pub use hud::*;
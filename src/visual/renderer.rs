@@ -0,0 +1,125 @@
+use web_sys::CanvasRenderingContext2d;
+
+pub trait Renderer {
+    fn save(&self);
+    fn restore(&self);
+
+    fn begin_path(&self);
+    fn move_to(&self, x: f64, y: f64);
+    fn line_to(&self, x: f64, y: f64);
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64);
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn close_path(&self);
+
+    fn fill(&self);
+    fn stroke(&self);
+    fn clip(&self);
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn fill_text(&self, text: &str, x: f64, y: f64);
+
+    fn set_fill_color(&self, color: &str);
+    fn set_stroke_color(&self, color: &str);
+    fn set_line_width(&self, width: f64);
+    fn set_global_alpha(&self, alpha: f64);
+    fn set_font(&self, font: &str);
+
+    fn translate(&self, x: f64, y: f64);
+    fn rotate(&self, angle: f64);
+    fn scale(&self, x: f64, y: f64);
+    fn set_transform(&self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64);
+}
+
+impl Renderer for CanvasRenderingContext2d {
+    fn save(&self) {
+        CanvasRenderingContext2d::save(self);
+    }
+
+    fn restore(&self) {
+        CanvasRenderingContext2d::restore(self);
+    }
+
+    fn begin_path(&self) {
+        CanvasRenderingContext2d::begin_path(self);
+    }
+
+    fn move_to(&self, x: f64, y: f64) {
+        CanvasRenderingContext2d::move_to(self, x, y);
+    }
+
+    fn line_to(&self, x: f64, y: f64) {
+        CanvasRenderingContext2d::line_to(self, x, y);
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        let _ = CanvasRenderingContext2d::arc(self, x, y, radius, start_angle, end_angle);
+    }
+
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        CanvasRenderingContext2d::rect(self, x, y, width, height);
+    }
+
+    fn close_path(&self) {
+        CanvasRenderingContext2d::close_path(self);
+    }
+
+    fn fill(&self) {
+        CanvasRenderingContext2d::fill(self);
+    }
+
+    fn stroke(&self) {
+        CanvasRenderingContext2d::stroke(self);
+    }
+
+    fn clip(&self) {
+        CanvasRenderingContext2d::clip(self);
+    }
+
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        CanvasRenderingContext2d::fill_rect(self, x, y, width, height);
+    }
+
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        CanvasRenderingContext2d::stroke_rect(self, x, y, width, height);
+    }
+
+    fn fill_text(&self, text: &str, x: f64, y: f64) {
+        let _ = CanvasRenderingContext2d::fill_text(self, text, x, y);
+    }
+
+    fn set_fill_color(&self, color: &str) {
+        self.set_fill_style_str(color);
+    }
+
+    fn set_stroke_color(&self, color: &str) {
+        self.set_stroke_style_str(color);
+    }
+
+    fn set_line_width(&self, width: f64) {
+        CanvasRenderingContext2d::set_line_width(self, width);
+    }
+
+    fn set_global_alpha(&self, alpha: f64) {
+        CanvasRenderingContext2d::set_global_alpha(self, alpha);
+    }
+
+    fn set_font(&self, font: &str) {
+        CanvasRenderingContext2d::set_font(self, font);
+    }
+
+    fn translate(&self, x: f64, y: f64) {
+        let _ = CanvasRenderingContext2d::translate(self, x, y);
+    }
+
+    fn rotate(&self, angle: f64) {
+        let _ = CanvasRenderingContext2d::rotate(self, angle);
+    }
+
+    fn scale(&self, x: f64, y: f64) {
+        let _ = CanvasRenderingContext2d::scale(self, x, y);
+    }
+
+    fn set_transform(&self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+        let _ = CanvasRenderingContext2d::set_transform(self, a, b, c, d, e, f);
+    }
+}
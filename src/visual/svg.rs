@@ -0,0 +1,370 @@
+use std::cell::RefCell;
+use std::fmt::Write;
+
+use crate::{geo::Point, Renderer};
+
+#[derive(Clone)]
+struct SvgState {
+    fill: String,
+    stroke: String,
+    line_width: f64,
+    alpha: f64,
+    font: String,
+    transform: [f64; 6],
+    clip: Option<usize>,
+}
+
+impl SvgState {
+    fn apply(&self, x: f64, y: f64) -> Point {
+        let [a, b, c, d, e, f] = self.transform;
+        Point::new(a * x + c * y + e, b * x + d * y + f)
+    }
+
+    fn scale(&self) -> f64 {
+        let [a, b, c, d, _, _] = self.transform;
+        (a * d - b * c).abs().sqrt()
+    }
+
+    fn multiply(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+        let [m_a, m_b, m_c, m_d, m_e, m_f] = self.transform;
+
+        self.transform = [
+            m_a * a + m_c * b,
+            m_b * a + m_d * b,
+            m_a * c + m_c * d,
+            m_b * c + m_d * d,
+            m_a * e + m_c * f + m_e,
+            m_b * e + m_d * f + m_f,
+        ];
+    }
+}
+
+struct SvgDocument {
+    state: SvgState,
+    stack: Vec<SvgState>,
+    path: String,
+    elements: Vec<String>,
+    clips: usize,
+}
+
+pub struct SvgRenderer {
+    pub width: f64,
+    pub height: f64,
+    document: RefCell<SvgDocument>,
+}
+
+impl SvgRenderer {
+    pub fn new(width: f64, height: f64) -> SvgRenderer {
+        let state = SvgState {
+            fill: "black".to_owned(),
+            stroke: "black".to_owned(),
+            line_width: 1.0,
+            alpha: 1.0,
+            font: "10px sans-serif".to_owned(),
+            transform: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+            clip: None,
+        };
+
+        SvgRenderer {
+            width,
+            height,
+            document: RefCell::new(SvgDocument {
+                state,
+                stack: vec![],
+                path: String::new(),
+                elements: vec![],
+                clips: 0,
+            }),
+        }
+    }
+
+    pub fn finish(&self) -> String {
+        let document = self.document.borrow();
+        let mut svg = String::new();
+
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.width,
+            h = self.height,
+        );
+        document.elements.iter().for_each(|e| { let _ = writeln!(svg, "  {}", e); });
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+
+    fn push_point(&self, command: char, x: f64, y: f64) {
+        let mut document = self.document.borrow_mut();
+        let point = document.state.apply(x, y);
+        let _ = write!(document.path, "{}{:.2} {:.2} ", command, point.x, point.y);
+    }
+
+    fn push_element(&self, element: &str, attributes: String, content: Option<String>) {
+        let mut document = self.document.borrow_mut();
+        let clip = match document.state.clip {
+            Some(id) => format!(r#" clip-path="url(#clip{})""#, id),
+            None => String::new(),
+        };
+
+        let element = match content {
+            Some(content) => format!("<{0}{1}{2}>{3}</{0}>", element, attributes, clip, content),
+            None => format!("<{}{}{}/>", element, attributes, clip),
+        };
+
+        document.elements.push(element);
+    }
+
+    fn paint(&self, fill: bool) {
+        let (path, state) = {
+            let document = self.document.borrow();
+            (document.path.trim_end().to_owned(), document.state.clone())
+        };
+
+        if path.is_empty() || (fill && state.fill == "transparent") {
+            return;
+        }
+
+        let attributes = if fill {
+            format!(r#" d="{}" fill="{}" fill-opacity="{}" stroke="none""#, path, escape(&state.fill), state.alpha)
+        } else {
+            format!(
+                r#" d="{}" fill="none" stroke="{}" stroke-opacity="{}" stroke-width="{:.2}""#,
+                path, escape(&state.stroke), state.alpha, state.line_width * state.scale()
+            )
+        };
+
+        self.push_element("path", attributes, None);
+    }
+}
+
+impl Renderer for SvgRenderer {
+    fn save(&self) {
+        let mut document = self.document.borrow_mut();
+        let state = document.state.clone();
+        document.stack.push(state);
+    }
+
+    fn restore(&self) {
+        let mut document = self.document.borrow_mut();
+        if let Some(state) = document.stack.pop() {
+            document.state = state;
+        }
+    }
+
+    fn begin_path(&self) {
+        self.document.borrow_mut().path.clear();
+    }
+
+    fn move_to(&self, x: f64, y: f64) {
+        self.push_point('M', x, y);
+    }
+
+    fn line_to(&self, x: f64, y: f64) {
+        self.push_point('L', x, y);
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        // approximated with a polyline so it follows any transform
+        let segments = 32;
+        let sweep = end_angle - start_angle;
+        let starts_path = self.document.borrow().path.is_empty();
+
+        for i in 0..=segments {
+            let angle = start_angle + sweep * i as f64 / segments as f64;
+            let command = if i == 0 && starts_path { 'M' } else { 'L' };
+            self.push_point(command, x + radius * angle.cos(), y + radius * angle.sin());
+        }
+    }
+
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.move_to(x, y);
+        self.line_to(x + width, y);
+        self.line_to(x + width, y + height);
+        self.line_to(x, y + height);
+        self.close_path();
+    }
+
+    fn close_path(&self) {
+        self.document.borrow_mut().path.push_str("Z ");
+    }
+
+    fn fill(&self) {
+        self.paint(true);
+    }
+
+    fn stroke(&self) {
+        self.paint(false);
+    }
+
+    fn clip(&self) {
+        let id = {
+            let mut document = self.document.borrow_mut();
+            document.clips += 1;
+            document.clips
+        };
+
+        let path = self.document.borrow().path.trim_end().to_owned();
+        self.document.borrow_mut().elements.push(format!(r#"<clipPath id="clip{}"><path d="{}"/></clipPath>"#, id, path));
+        self.document.borrow_mut().state.clip = Some(id);
+    }
+
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        let path = std::mem::take(&mut self.document.borrow_mut().path);
+        self.rect(x, y, width, height);
+        self.fill();
+        self.document.borrow_mut().path = path;
+    }
+
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        let path = std::mem::take(&mut self.document.borrow_mut().path);
+        self.rect(x, y, width, height);
+        self.stroke();
+        self.document.borrow_mut().path = path;
+    }
+
+    fn fill_text(&self, text: &str, x: f64, y: f64) {
+        let state = self.document.borrow().state.clone();
+        let point = state.apply(x, y);
+
+        let attributes = format!(
+            r#" x="{:.2}" y="{:.2}" fill="{}" fill-opacity="{}" style="font: {}""#,
+            point.x, point.y, escape(&state.fill), state.alpha, escape(&state.font)
+        );
+
+        self.push_element("text", attributes, Some(escape(text)));
+    }
+
+    fn set_fill_color(&self, color: &str) {
+        self.document.borrow_mut().state.fill = color.to_owned();
+    }
+
+    fn set_stroke_color(&self, color: &str) {
+        self.document.borrow_mut().state.stroke = color.to_owned();
+    }
+
+    fn set_line_width(&self, width: f64) {
+        self.document.borrow_mut().state.line_width = width;
+    }
+
+    fn set_global_alpha(&self, alpha: f64) {
+        self.document.borrow_mut().state.alpha = alpha;
+    }
+
+    fn set_font(&self, font: &str) {
+        self.document.borrow_mut().state.font = font.to_owned();
+    }
+
+    fn translate(&self, x: f64, y: f64) {
+        self.document.borrow_mut().state.multiply(1.0, 0.0, 0.0, 1.0, x, y);
+    }
+
+    fn rotate(&self, angle: f64) {
+        let (sin, cos) = angle.sin_cos();
+        self.document.borrow_mut().state.multiply(cos, sin, -sin, cos, 0.0, 0.0);
+    }
+
+    fn scale(&self, x: f64, y: f64) {
+        self.document.borrow_mut().state.multiply(x, 0.0, 0.0, y, 0.0, 0.0);
+    }
+
+    fn set_transform(&self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+        self.document.borrow_mut().state.transform = [a, b, c, d, e, f];
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path_of(renderer: &SvgRenderer) -> String {
+        renderer.document.borrow().path.trim_end().to_owned()
+    }
+
+    #[test]
+    fn transforms_compose_like_canvas() {
+        let renderer = SvgRenderer::new(100.0, 100.0);
+
+        // later calls apply first, so the point is rotated and then translated
+        renderer.translate(10.0, 20.0);
+        renderer.rotate(std::f64::consts::FRAC_PI_2);
+        renderer.move_to(1.0, 0.0);
+        assert_eq!(path_of(&renderer), "M10.00 21.00");
+
+        renderer.scale(2.0, 3.0);
+        renderer.line_to(1.0, 1.0);
+        assert_eq!(path_of(&renderer), "M10.00 21.00 L7.00 22.00");
+    }
+
+    #[test]
+    fn multiply_matches_successive_transforms() {
+        let renderer = SvgRenderer::new(100.0, 100.0);
+        renderer.translate(5.0, -5.0);
+        renderer.scale(2.0, 2.0);
+
+        let mut state = SvgRenderer::new(100.0, 100.0).document.borrow().state.clone();
+        state.multiply(2.0, 0.0, 0.0, 2.0, 5.0, -5.0);
+
+        assert_eq!(renderer.document.borrow().state.transform, state.transform);
+        assert_eq!(state.scale(), 2.0);
+    }
+
+    #[test]
+    fn restore_drops_the_clip_and_transform() {
+        let renderer = SvgRenderer::new(100.0, 100.0);
+
+        renderer.save();
+        renderer.translate(50.0, 50.0);
+        renderer.begin_path();
+        renderer.rect(0.0, 0.0, 10.0, 10.0);
+        renderer.clip();
+        renderer.fill_rect(0.0, 0.0, 1.0, 1.0);
+        renderer.restore();
+        renderer.fill_rect(0.0, 0.0, 1.0, 1.0);
+
+        let elements = &renderer.document.borrow().elements;
+        assert_eq!(elements.len(), 3);
+        assert!(elements[0].starts_with(r#"<clipPath id="clip1"><path d="M50.00 50.00"#));
+        assert!(elements[1].contains(r#"d="M50.00 50.00"#));
+        assert!(elements[1].contains(r#"clip-path="url(#clip1)""#));
+        assert!(elements[2].contains(r#"d="M0.00 0.00"#));
+        assert!(!elements[2].contains("clip-path"));
+    }
+
+    #[test]
+    fn fill_rect_keeps_the_current_path() {
+        let renderer = SvgRenderer::new(100.0, 100.0);
+
+        renderer.begin_path();
+        renderer.move_to(0.0, 0.0);
+        renderer.line_to(5.0, 5.0);
+        renderer.fill_rect(20.0, 20.0, 1.0, 1.0);
+        renderer.stroke_rect(30.0, 30.0, 1.0, 1.0);
+        assert_eq!(path_of(&renderer), "M0.00 0.00 L5.00 5.00");
+
+        renderer.stroke();
+        let elements = &renderer.document.borrow().elements;
+        assert_eq!(elements.len(), 3);
+        assert!(elements[2].contains(r#"d="M0.00 0.00 L5.00 5.00""#));
+    }
+
+    #[test]
+    fn text_and_attributes_are_escaped() {
+        assert_eq!(escape(r#"<a & "b">"#), "&lt;a &amp; &quot;b&quot;&gt;");
+
+        let renderer = SvgRenderer::new(100.0, 100.0);
+        renderer.set_fill_color(r#"x" onload="y"#);
+        renderer.fill_text("1 < 2 & 3", 0.0, 0.0);
+
+        let svg = renderer.finish();
+        assert!(svg.contains(r#"fill="x&quot; onload=&quot;y""#));
+        assert!(svg.contains(">1 &lt; 2 &amp; 3</text>"));
+    }
+}
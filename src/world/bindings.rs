@@ -0,0 +1,99 @@
+use crate::Controls;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ControlAction {
+    Forward,
+    Backward,
+    Left,
+    Right,
+    Brake,
+}
+
+impl ControlAction {
+    pub fn from_name(name: &str) -> Option<ControlAction> {
+        match name.to_lowercase().as_str() {
+            "forward" => Some(ControlAction::Forward),
+            "backward" => Some(ControlAction::Backward),
+            "left" => Some(ControlAction::Left),
+            "right" => Some(ControlAction::Right),
+            "brake" => Some(ControlAction::Brake),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, controls: &mut Controls, pressed: bool) {
+        match self {
+            ControlAction::Forward => controls.forward = pressed,
+            ControlAction::Backward => controls.backward = pressed,
+            ControlAction::Left => controls.left = pressed,
+            ControlAction::Right => controls.right = pressed,
+            ControlAction::Brake => controls.brake = pressed,
+        }
+    }
+}
+
+pub struct KeyBindings {
+    pub bindings: Vec<(String, ControlAction)>,
+}
+
+impl KeyBindings {
+    pub fn new() -> KeyBindings {
+        KeyBindings { bindings: vec![] }
+    }
+
+    pub fn wasd() -> KeyBindings {
+        let mut bindings = KeyBindings::new();
+        bindings.bind("w", ControlAction::Forward);
+        bindings.bind("s", ControlAction::Backward);
+        bindings.bind("a", ControlAction::Left);
+        bindings.bind("d", ControlAction::Right);
+        bindings.bind(" ", ControlAction::Brake);
+        bindings
+    }
+
+    pub fn arrows() -> KeyBindings {
+        let mut bindings = KeyBindings::new();
+        bindings.bind("ArrowUp", ControlAction::Forward);
+        bindings.bind("ArrowDown", ControlAction::Backward);
+        bindings.bind("ArrowLeft", ControlAction::Left);
+        bindings.bind("ArrowRight", ControlAction::Right);
+        bindings.bind("Enter", ControlAction::Brake);
+        bindings
+    }
+
+    pub fn bind(&mut self, key: &str, action: ControlAction) {
+        self.unbind_key(key);
+        self.bindings.push((key.to_owned(), action));
+    }
+
+    pub fn rebind(&mut self, action: ControlAction, key: &str) {
+        self.bindings.retain(|(_, a)| *a != action);
+        self.bind(key, action);
+    }
+
+    pub fn unbind_key(&mut self, key: &str) {
+        self.bindings.retain(|(k, _)| !KeyBindings::matches(k, key));
+    }
+
+    pub fn action(&self, key: &str) -> Option<ControlAction> {
+        self.bindings
+            .iter()
+            .find(|(k, _)| KeyBindings::matches(k, key))
+            .map(|(_, action)| *action)
+    }
+
+    fn matches(bound: &str, key: &str) -> bool {
+        // single characters ignore case so bindings keep working with shift or caps lock
+        if bound.chars().count() == 1 {
+            bound.eq_ignore_ascii_case(key)
+        } else {
+            bound == key
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings::new()
+    }
+}
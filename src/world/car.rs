@@ -1,36 +1,101 @@
-use web_sys::js_sys::Math;
+use std::collections::VecDeque;
 
 use crate::{
-    Drawable, 
-    geo::{Polygon, Point}, 
-    sprite, console_log, sensors::Sensors, Road
+    Drawable, Renderer,
+    geo::{Line, Point, Polygon},
+    grid::SweepHit,
+    helpers::{clampf, lerpf, wrap_angle},
+    sprite, sensors::{SensorNoise, SensorRig, SensorTargets, Sensors}, Road, PhysicsModel,
+    Checkpoint, CollisionResponse, Contact,
 };
 
+pub const TRAIL_SPACING: f64 = 5.0;
+pub const TRAIL_MAX_POINTS: usize = 2000;
+pub const SLIDE_CLEARANCE: f64 = 0.5;
+pub const CRASH_CLEAR_TICKS: u32 = 30;
+pub const SLIDE_TURNS: [f64; 4] = [1.0, 0.5, 0.25, 0.0];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ControlMode {
+    Digital,
+    Analog,
+}
+
 pub struct Controls {
+    pub mode: ControlMode,
+
     pub forward: bool,
     pub backward: bool,
     pub left: bool,
     pub right: bool,
     pub brake: bool,
+
+    pub steering: f64,
+    pub throttle: f64,
+    pub braking: f64,
 }
 
 impl Controls {
     pub fn new() -> Controls {
         Controls {
+            mode: ControlMode::Digital,
+
             forward: false,
             backward: false,
             left: false,
             right: false,
             brake: false,
+
+            steering: 0.0,
+            throttle: 0.0,
+            braking: 0.0,
         }
     }
 
+    pub fn analog() -> Controls {
+        let mut controls = Controls::new();
+        controls.mode = ControlMode::Analog;
+        controls
+    }
+
     pub fn reset(&mut self) {
         self.forward = false;
         self.backward = false;
         self.left = false;
         self.right = false;
         self.brake = false;
+
+        self.steering = 0.0;
+        self.throttle = 0.0;
+        self.braking = 0.0;
+    }
+
+    pub fn update_analog(&mut self, steering: f64, throttle: f64, braking: f64) {
+        self.mode = ControlMode::Analog;
+        self.steering = clampf(steering, -1.0, 1.0);
+        self.throttle = clampf(throttle, 0.0, 1.0);
+        self.braking = clampf(braking, 0.0, 1.0);
+    }
+
+    pub fn steering_input(&self) -> f64 {
+        match self.mode {
+            ControlMode::Digital => (self.right as i32 - self.left as i32) as f64,
+            ControlMode::Analog => self.steering,
+        }
+    }
+
+    pub fn throttle_input(&self) -> f64 {
+        match self.mode {
+            ControlMode::Digital => if self.forward { 1.0 } else { 0.0 },
+            ControlMode::Analog => self.throttle,
+        }
+    }
+
+    pub fn brake_input(&self) -> f64 {
+        match self.mode {
+            ControlMode::Digital => if self.brake { 1.0 } else { 0.0 },
+            ControlMode::Analog => self.braking,
+        }
     }
 
     pub fn update(&mut self, forward: bool, backward: bool, left: bool, right: bool, brake: bool) {
@@ -42,15 +107,15 @@ impl Controls {
     }
 
     pub fn is_active(&self) -> bool {
-        self.forward || self.backward || self.left || self.right || self.brake
+        self.is_moving() || self.is_turning() || self.brake_input() > 0.0
     }
 
     pub fn is_moving(&self) -> bool {
-        self.forward || self.backward
+        self.throttle_input() > 0.0 || self.backward
     }
 
     pub fn is_turning(&self) -> bool {
-        self.left || self.right
+        self.steering_input() != 0.0
     }
 }
 
@@ -65,34 +130,58 @@ pub struct Car {
     pub max_speed: f64,
     pub acceleration: f64,
     pub friction: f64,
+    pub physics: PhysicsModel,
 
     pub polygons: Vec<Polygon>,
     pub controls: Controls,
+    pub shape: Polygon,
     pub hitbox: Polygon,
 
     pub sensors: Sensors,    
+    pub trail: VecDeque<Point>,
+    pub impact: Option<SweepHit>,
+    pub response: CollisionResponse,
+    pub checkpoint: Checkpoint,
+    pub crashes: u32,
+    pub crash_point: Option<Point>,
+    // ticks since the car last touched anything
+    pub clear_ticks: u32,
     // pub brain: Brain,
     // pub fitness: f64,
     pub has_collided: bool,
+
+    candidates: Vec<usize>,
 }
 
 impl Car {
     pub fn new(x: f64, y: f64, width: f64, height: f64, angle: f64, speed: f64, max_speed: f64, acceleration: f64, friction: f64) -> Car {
-        let polygons = Car::generate_polygons(width, height, angle);
+        let polygons = Car::generate_polygons(width, height);
         let controls = Controls::new();
 
-        let mut hitbox = Polygon::rectangle(x, y, height, width, angle);
-        hitbox.fill_color = "transparent".to_owned();
-        hitbox.scale(1.0, 1.0);
+        let mut shape = Polygon::rectangle(0.0, 0.0, height, width, 0.0);
+        shape.fill_color = "transparent".to_owned();
 
-        let sensors = Sensors::new(x, y, 7, 200.0, std::f64::consts::PI, angle);
+        let mut hitbox = Polygon::new(vec![], shape.fill_color.clone());
+        hitbox.transform_from(&shape, x, y, angle);
+
+        let sensors = Sensors::from_rig(SensorRig::default().with_body(height, width), x, y, angle);
 
         Car {
             x, y, width, height, angle, 
             speed, max_speed, acceleration, friction, 
-            polygons, controls, hitbox,
+            physics: PhysicsModel::Arcade,
+            polygons, controls, shape, hitbox,
             sensors,
+            trail: VecDeque::from(vec![Point::new(x, y)]),
+            impact: None,
+            response: CollisionResponse::Stop,
+            checkpoint: Checkpoint::new(x, y, angle),
+            crashes: 0,
+            crash_point: None,
+            clear_ticks: CRASH_CLEAR_TICKS,
             has_collided: false,
+
+            candidates: vec![],
         }
     } 
 
@@ -100,20 +189,121 @@ impl Car {
         self.has_collided = true;
         self.speed = 0.0;
         self.controls.reset();
+        self.physics.reset();
+    }
+
+    pub fn with_bicycle_physics(mut self) -> Car {
+        self.physics = PhysicsModel::bicycle(self.height * 0.6);
+        self
+    }
+
+    pub fn with_sensor_rig(mut self, rig: SensorRig) -> Car {
+        self.set_sensor_rig(rig);
+        self
+    }
+
+    pub fn set_sensor_rig(&mut self, rig: SensorRig) {
+        self.sensors = Sensors::from_rig(rig, self.x, self.y, self.angle);
+    }
+
+    pub fn with_collision_response(mut self, response: CollisionResponse) -> Car {
+        self.response = response;
+        self
+    }
+
+    pub fn with_sensor_noise(mut self, noise: SensorNoise) -> Car {
+        self.sensors.set_noise(Some(noise));
+        self
     }
 
     pub fn new_at(x: f64, y: f64) -> Car {
         let mut default_car = Car::default();
         default_car.x = x; 
         default_car.y = y;
-
-        default_car.hitbox.translate(x, y);
-        default_car.polygons.iter_mut().for_each(|p| p.translate(x, y));
-        default_car.sensors.translate(x, y);
+        default_car.update_geometry();
+        default_car.reset_trail();
+        default_car.reset_checkpoint();
 
         default_car 
     }
 
+    pub fn set_color(&mut self, color: &str) {
+        if let Some(body) = self.polygons.get_mut(sprite::CAR_BODY) {
+            body.fill_color = color.to_owned();
+        }
+    }
+
+    pub fn set_pose(&mut self, x: f64, y: f64, angle: f64) {
+        self.x = x;
+        self.y = y;
+        self.angle = angle;
+        self.update_geometry();
+    }
+
+    pub fn reset_checkpoint(&mut self) {
+        self.checkpoint = Checkpoint::new(self.x, self.y, self.angle);
+    }
+
+    pub fn update_checkpoint(&mut self, road: &Road) {
+        let Some(position) = road.locate(&Point::new(self.x, self.y)) else { return };
+        if position.index == self.checkpoint.index { return }
+
+        if let Some(checkpoint) = Checkpoint::on_segment(road, position.index) {
+            self.checkpoint = checkpoint;
+        }
+    }
+
+    pub fn respawn(&mut self) {
+        let Checkpoint { x, y, angle, .. } = self.checkpoint;
+
+        self.set_pose(x, y, angle);
+        self.speed = 0.0;
+        self.physics.reset();
+        self.reset_trail();
+        // back on the track, so the next contact is a crash of its own
+        self.clear_ticks = CRASH_CLEAR_TICKS;
+    }
+
+    pub fn update_geometry(&mut self) {
+        self.hitbox.transform_from(&self.shape, self.x, self.y, self.angle);
+        self.sensors.set_pose(self.x, self.y, self.angle);
+    }
+
+    pub fn record_trail(&mut self) {
+        let position = Point::new(self.x, self.y);
+        if self.trail.back().is_some_and(|last| last.distance(&position) < TRAIL_SPACING) { return; }
+
+        if self.trail.len() >= TRAIL_MAX_POINTS {
+            self.trail.pop_front();
+        }
+        self.trail.push_back(position);
+    }
+
+    pub fn reset_trail(&mut self) {
+        self.trail.clear();
+        self.trail.push_back(Point::new(self.x, self.y));
+    }
+
+    pub fn draw_trail(&self, context: &dyn Renderer, color: &str) {
+        if self.trail.len() < 2 { return; }
+
+        context.save();
+        context.set_stroke_color(color);
+        context.set_line_width(2.0);
+
+        // older segments fade out towards the start of the trail
+        let count = self.trail.len() - 1;
+        for (i, (start, end)) in self.trail.iter().zip(self.trail.iter().skip(1)).enumerate() {
+            context.set_global_alpha(0.05 + 0.75 * (i + 1) as f64 / count as f64);
+            context.begin_path();
+            context.move_to(start.x, start.y);
+            context.line_to(end.x, end.y);
+            context.stroke();
+        }
+
+        context.restore();
+    }
+
     pub fn accelerate(&mut self) {
         self.speed += self.acceleration;
         
@@ -124,8 +314,12 @@ impl Car {
     }
 
     pub fn brake(&mut self) {
-        let brake_force = self.acceleration * 2.5;
-        let new_speed = Math::max(Math::abs(self.speed) - brake_force, 0.0);
+        self.brake_with(1.0);
+    }
+
+    pub fn brake_with(&mut self, amount: f64) {
+        let brake_force = self.acceleration * 2.5 * amount;
+        let new_speed = (self.speed.abs() - brake_force).max(0.0);
 
         if self.speed >= 0.0 {
             self.speed = new_speed;
@@ -137,11 +331,6 @@ impl Car {
     pub fn turn(&mut self, d_angle: f64) {
         let d_angle = if self.speed >= 0.0 { d_angle } else { -d_angle }; 
         self.angle += d_angle;
-        self.hitbox.rotate(d_angle);
-
-        let car_origin = Point::new(self.x, self.y);
-        self.polygons.iter_mut().for_each(|p| p.rotate_around(d_angle, &car_origin));
-        self.sensors.rotate(d_angle);
     }
 
     pub fn turn_left(&mut self) {
@@ -157,7 +346,7 @@ impl Car {
             self.speed = self.max_speed;
         } else if self.speed < -self.max_speed {
             self.speed = -self.max_speed;
-        } else if Math::abs(self.speed) < Math::abs(self.friction) {
+        } else if self.speed.abs() < self.friction.abs() {
             self.speed = 0.0;
         } else if self.speed > 0.0 {
             self.speed -= self.friction;
@@ -167,52 +356,204 @@ impl Car {
     }
 
     pub fn move_coords(&mut self) {
-        if self.speed == 0.0 { return; }
+        if self.speed == 0.0 && self.physics.lateral_speed() == 0.0 { return; }
 
-        let d_x = self.speed * self.angle.cos();
-        let d_y = self.speed * self.angle.sin();
+        let lateral_speed = self.physics.lateral_speed();
+        let d_x = self.speed * self.angle.cos() - lateral_speed * self.angle.sin();
+        let d_y = self.speed * self.angle.sin() + lateral_speed * self.angle.cos();
 
         self.x += d_x;
         self.y += d_y;
-
-        self.hitbox.translate(d_x, d_y);
-        self.polygons.iter_mut().for_each(|p| p.translate(d_x, d_y));
-        self.sensors.translate(d_x, d_y);
     }
 
     pub fn apply_controls(&mut self) {
-        if self.controls.forward { self.accelerate(); }
+        let throttle = self.controls.throttle_input();
+        let steering = self.controls.steering_input();
+        let braking = self.controls.brake_input();
+
+        if throttle > 0.0 { self.speed += self.acceleration * throttle; }
         if self.controls.backward { self.decelerate(); }
-        if self.controls.left { self.turn_left(); }
-        if self.controls.right { self.turn_right(); }
-        if self.controls.brake { self.brake(); }
+
+        match &mut self.physics {
+            PhysicsModel::Arcade => {
+                if steering != 0.0 { self.turn(0.1 * steering); }
+            }
+            PhysicsModel::Bicycle(model) => {
+                self.angle += model.step(steering, &mut self.speed, self.max_speed);
+            }
+        }
+
+        if braking > 0.0 { self.brake_with(braking); }
 
         self.limit_speed();
     }
 
     
     pub fn update(&mut self, road: &Road) {
+        self.update_with(road, &SensorTargets::default());
+    }
+
+    pub fn update_with(&mut self, road: &Road, targets: &SensorTargets) {
         if self.has_collided { return; }
 
+        let previous_pose = (self.x, self.y, self.angle);
+        let previous_hitbox = self.hitbox.clone();
+
         self.apply_controls();
         self.move_coords();
-        self.sensors.update(&road);
-        self.check_collisions(road);
+        self.update_geometry();
+        self.check_collisions(road, targets, previous_pose, &previous_hitbox);
+        // every response can end up respawning a car that left the track
+        if !self.has_collided {
+            self.update_checkpoint(road);
+        }
+        self.record_trail();
+        self.sensors.update(road, targets, &self.hitbox, self.speed / self.max_speed);
+    }
+
+    pub fn check_collisions(&mut self, road: &Road, targets: &SensorTargets, previous_pose: (f64, f64, f64), previous_hitbox: &Polygon) {
+        // sweep the corners along this tick's motion so fast cars can't skip over an edge
+        let contact = if let Some(hit) = road.grid.sweep_outline(&previous_hitbox.points, &self.hitbox.points, &mut self.candidates) {
+            let (x, y, angle) = previous_pose;
+            self.set_pose(lerpf(x, self.x, hit.time), lerpf(y, self.y, hit.time), lerpf(angle, self.angle, hit.time));
+
+            self.impact = Some(hit);
+            Contact::Wall(road.grid.lines[hit.line])
+        } else if let Some(line) = road.grid.crossed_line(&self.hitbox.points, &self.candidates) {
+            self.impact = None;
+            Contact::Wall(road.grid.lines[line])
+        } else if road.is_off_track(&self.hitbox.points) {
+            self.impact = None;
+            Contact::OffTrack
+        } else if let Some(body) = self.hit_target(targets, previous_hitbox) {
+            self.impact = None;
+            Contact::Body(body)
+        } else {
+            self.clear_ticks = self.clear_ticks.saturating_add(1);
+            return;
+        };
+
+        // a car pressed against a wall keeps touching it every few ticks,
+        // that only becomes a new crash once it has been clear for a while
+        if self.clear_ticks >= CRASH_CLEAR_TICKS {
+            // remembered before responding, which may move the car away from the wall
+            self.crashes += 1;
+            self.crash_point = Some(Point::new(self.x, self.y));
+        }
+        self.clear_ticks = 0;
+        self.respond(road, contact, previous_pose);
+    }
+
+    pub fn overlaps(&self, polygon: &Polygon) -> bool {
+        // the hitbox is a rectangle, so SAT is exact for convex outlines and
+        // containment still catches a car that ended up inside a concave one
+        self.hitbox.overlaps_convex(polygon) || polygon.contains_polygon(&self.hitbox)
+    }
+
+    // other cars only count when the overlap is new, cars sharing a spawn point start out stacked
+    fn hit_target<'a>(&self, targets: &SensorTargets<'a>, previous_hitbox: &Polygon) -> Option<&'a Polygon> {
+        let hit_car = targets.cars.iter()
+            .enumerate()
+            .filter(|(i, _)| targets.exclude != Some(*i))
+            .find(|(_, car)| self.overlaps(car) && !previous_hitbox.overlaps_convex(car))
+            .map(|(_, car)| car);
+
+        hit_car.or_else(|| targets.obstacles.iter().find(|obstacle| self.overlaps(obstacle)))
+    }
+
+    pub fn respond(&mut self, road: &Road, contact: Contact, previous_pose: (f64, f64, f64)) {
+        let (x, y, angle) = previous_pose;
+
+        match (self.response, contact) {
+            (CollisionResponse::Stop, _) => self.collide(),
+            // there is nothing to push against off the track, bring the car back
+            (CollisionResponse::Respawn, _) | (_, Contact::OffTrack) => self.respawn(),
+            (CollisionResponse::Bounce { restitution }, _) => {
+                self.set_pose(x, y, angle);
+                self.speed = -self.speed * restitution;
+                self.physics.reset();
+            }
+            (CollisionResponse::Slide { speed_loss }, Contact::Wall(wall)) => {
+                self.slide(road, &wall, None, speed_loss, previous_pose);
+            }
+            (CollisionResponse::Slide { speed_loss }, Contact::Body(body)) => {
+                // slide along the side of the body the car came from
+                let origin = Point::new(x, y);
+                let side = body.lines().into_iter().min_by(|a, b| {
+                    let a = a.point_at(a.project(&origin)).distance(&origin);
+                    let b = b.point_at(b.project(&origin)).distance(&origin);
+                    a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+                });
+
+                if let Some(side) = side {
+                    self.slide(road, &side, Some(body), speed_loss, previous_pose);
+                }
+            }
+        }
+    }
+
+    fn slide(&mut self, road: &Road, wall: &Line, body: Option<&Polygon>, speed_loss: f64, previous_pose: (f64, f64, f64)) {
+        let (x, y, angle) = previous_pose;
+        let (impact_x, impact_y, impact_angle, speed) = (self.x, self.y, self.angle, self.speed);
+
+        // near a corner turning all the way may swing the car into the next wall,
+        // so it turns as far as fits and straightens out over the next ticks
+        for turn in SLIDE_TURNS {
+            self.slide_along(wall, speed_loss, turn, (x, y));
+            if !road.grid.intersects_outline(&self.hitbox.points, &mut self.candidates) && !body.is_some_and(|body| self.overlaps(body)) {
+                self.physics.reset();
+                return;
+            }
+
+            self.set_pose(impact_x, impact_y, impact_angle);
+            self.speed = speed;
+        }
+
+        self.set_pose(x, y, angle);
+        self.speed = 0.0;
+        self.physics.reset();
+    }
+
+    // keeps only the part of the velocity along the wall and carries on from the impact pose,
+    // `origin` is a point on the side of the wall the car came from
+    fn slide_along(&mut self, wall: &Line, speed_loss: f64, turn: f64, origin: (f64, f64)) {
+        let mut heading = wall.angle();
+        if (self.angle - heading).cos() < 0.0 {
+            heading += std::f64::consts::PI;
+        }
+
+        let (sin, cos) = wall.angle().sin_cos();
+        let mut normal = Point::new(-sin, cos);
+        if (origin.0 - wall.start.x) * normal.x + (origin.1 - wall.start.y) * normal.y < 0.0 {
+            normal = Point::new(sin, -cos);
+        }
+
+        self.speed *= (self.angle - heading).cos() * (1.0 - speed_loss);
+
+        // out of the wall first, then turned towards it, then moved along it
+        self.push_out(wall, &normal);
+        self.set_pose(self.x, self.y, self.angle + wrap_angle(heading - self.angle) * turn);
+        self.push_out(wall, &normal);
+        self.set_pose(self.x + self.speed * heading.cos(), self.y + self.speed * heading.sin(), self.angle);
     }
 
-    pub fn check_collisions(&mut self, road: &Road) {
-        if self.hitbox.intersects(&road.hitbox) {
-            self.collide();
+    fn push_out(&mut self, wall: &Line, normal: &Point) {
+        let depth = self.hitbox.points.iter()
+            .map(|p| (p.x - wall.start.x) * normal.x + (p.y - wall.start.y) * normal.y)
+            .fold(f64::INFINITY, f64::min);
+
+        if depth < SLIDE_CLEARANCE {
+            let distance = SLIDE_CLEARANCE - depth;
+            self.set_pose(self.x + normal.x * distance, self.y + normal.y * distance, self.angle);
         }
     }
 
-    pub fn generate_polygons(width: f64, height: f64, angle: f64) -> Vec<Polygon> {
+    pub fn generate_polygons(width: f64, height: f64) -> Vec<Polygon> {
         let mut sprite = sprite::get_car_sprite(); 
 
         sprite.iter_mut().for_each(|poly| {
             poly.translate(-50.0, -25.0); 
             poly.scale_origin(height / 100.0, width / 50.0); 
-            poly.rotate_origin(angle);
         });
 
         sprite
@@ -220,10 +561,15 @@ impl Car {
 }
 
 impl Drawable for Car {
-    fn draw(&self, context: &web_sys::CanvasRenderingContext2d) {
+    fn draw(&self, context: &dyn Renderer) {
         // self.hitbox.draw(context);
         self.sensors.draw(context);
+
+        context.save();
+        context.translate(self.x, self.y);
+        context.rotate(self.angle);
         self.polygons.draw(context);
+        context.restore();
     }
 }
 
@@ -245,3 +591,111 @@ impl Default for Car{
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square_road() -> Road {
+        let mut road = Road::new();
+        for (x, y) in [(0.0, 0.0), (1000.0, 0.0), (1000.0, 1000.0), (0.0, 1000.0)] {
+            road.add_point(Point::new(x, y), 100.0);
+        }
+        road.construct();
+        road
+    }
+
+    #[test]
+    fn sliding_car_moves_along_the_wall() {
+        let road = square_road();
+        let mut car = Car::new_at(300.0, 0.0).with_collision_response(CollisionResponse::Slide { speed_loss: 0.1 });
+        car.set_pose(300.0, 0.0, 0.5);
+        car.speed = 8.0;
+
+        while car.crashes == 0 {
+            car.update(&road);
+        }
+        let impact = car.crash_point.unwrap();
+        for _ in 0..20 {
+            car.update(&road);
+        }
+
+        assert!(!car.has_collided);
+        assert!(car.x > impact.x + 50.0);
+        assert!(car.y < 100.0 - car.width / 2.0 + 1.0);
+        assert!(car.angle.cos() > 0.99);
+    }
+
+    #[test]
+    fn sliding_car_gets_round_a_corner() {
+        let entities = crate::Entities::with_road(Road::load());
+        let mut cars = entities.cars.borrow_mut();
+        let car = &mut cars[0];
+        car.response = CollisionResponse::Slide { speed_loss: 0.2 };
+        car.controls.forward = true;
+
+        // the first corner on the default track used to pin a car holding forward against it
+        let corner = entities.road.progress(&Point::new(733.0, 771.0));
+        for _ in 0..300 {
+            car.update(&entities.road);
+        }
+
+        assert!(car.crashes > 0);
+        assert!(entities.road.progress(&Point::new(car.x, car.y)) > corner + 200.0);
+    }
+
+    #[test]
+    fn pressing_into_a_wall_counts_one_crash() {
+        let road = square_road();
+        let mut car = Car::new_at(300.0, 0.0).with_collision_response(CollisionResponse::Bounce { restitution: 0.5 });
+        car.set_pose(300.0, 0.0, std::f64::consts::FRAC_PI_2);
+        car.controls.forward = true;
+
+        for _ in 0..600 {
+            car.update(&road);
+        }
+
+        assert_eq!(car.crashes, 1);
+    }
+
+    #[test]
+    fn bumping_another_car_does_not_respawn() {
+        let road = square_road();
+        let parked = Car::new_at(450.0, 30.0).hitbox;
+        let cars = [parked.clone()];
+        let targets = SensorTargets::new(&cars, &[]);
+
+        for response in [CollisionResponse::Bounce { restitution: 0.5 }, CollisionResponse::Slide { speed_loss: 0.2 }] {
+            let mut car = Car::new_at(300.0, -20.0).with_collision_response(response);
+            car.set_pose(300.0, -20.0, 0.35);
+            car.speed = 6.0;
+
+            while car.crashes == 0 && car.x < 450.0 {
+                car.update_with(&road, &targets);
+            }
+
+            assert_eq!(car.crashes, 1, "{:?}", response);
+            assert!(car.trail.len() > 1, "{:?} respawned", response);
+            assert!(!car.overlaps(&parked), "{:?}", response);
+        }
+    }
+
+    #[test]
+    fn sensors_ignore_cars_stacked_on_top() {
+        let road = square_road();
+        let mut car = Car::new_at(300.0, 0.0);
+        let cars = [car.hitbox.clone(), Car::new_at(300.0, 0.0).hitbox, Car::new_at(450.0, 0.0).hitbox];
+
+        car.update_with(&road, &SensorTargets::new(&cars, &[]).excluding(0));
+
+        let car_hits: Vec<f64> = car.sensors.sensors.iter()
+            .filter_map(|s| s.reading)
+            .filter(|r| r.kind == crate::sensors::HitKind::Car)
+            .map(|r| r.offset)
+            .collect();
+
+        // only the car ahead is seen, and not from inside the car sharing the spawn
+        assert!(!car_hits.is_empty());
+        assert!(car_hits.iter().all(|&offset| offset > 0.1));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{geo::{Line, Point, Polygon}, Road};
+
+pub const CHECKPOINT_OFFSET: f64 = 50.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CollisionResponse {
+    #[default]
+    Stop,
+    Bounce { restitution: f64 },
+    Slide { speed_loss: f64 },
+    Respawn,
+}
+
+impl CollisionResponse {
+    // `amount` is the restitution for bouncing and the fraction of speed lost when sliding,
+    // anything outside [0, 1] would reverse the car instead
+    pub fn from_name(name: &str, amount: f64) -> Option<CollisionResponse> {
+        let in_range = (0.0..=1.0).contains(&amount);
+
+        match name.to_lowercase().as_str() {
+            "stop" => Some(CollisionResponse::Stop),
+            "bounce" if in_range => Some(CollisionResponse::Bounce { restitution: amount }),
+            "slide" if in_range => Some(CollisionResponse::Slide { speed_loss: amount }),
+            "respawn" => Some(CollisionResponse::Respawn),
+            _ => None,
+        }
+    }
+}
+
+// what a car ran into this tick
+#[derive(Clone, Copy, Debug)]
+pub enum Contact<'a> {
+    Wall(Line),
+    Body(&'a Polygon),
+    OffTrack,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub index: usize,
+    pub x: f64,
+    pub y: f64,
+    pub angle: f64,
+}
+
+impl Checkpoint {
+    pub fn new(x: f64, y: f64, angle: f64) -> Checkpoint {
+        Checkpoint { index: usize::MAX, x, y, angle }
+    }
+
+    // a checkpoint sits a little way into every road segment, facing along it
+    pub fn on_segment(road: &Road, index: usize) -> Option<Checkpoint> {
+        let line = &road.lines.get(index)?.line;
+        let t = if line.length() > 0.0 { (CHECKPOINT_OFFSET / line.length()).min(0.5) } else { 0.0 };
+        let Point { x, y } = line.point_at(t);
+
+        Some(Checkpoint { index, x, y, angle: line.angle() })
+    }
+}
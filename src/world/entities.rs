@@ -1,56 +1,219 @@
 use std::{cell::RefCell, rc::Rc};
 
-use crate::{world::*, Drawable, console_log};
+use crate::{world::*, bindings::KeyBindings, sensors::{SensorNoise, SensorRig, SensorTargets}, geo::{Point, Polygon}, snapshot, Drawable, Renderer};
 use self::player::Player;
 
 pub struct Entities {
     pub cars: Rc<RefCell<Vec<Car>>>,
-    pub player: Option<Player>,
+    pub players: Vec<Player>,
+    pub fleet: Fleet,
     pub road: Road,
     pub finish_line: FinishLine,
+    pub obstacles: Vec<Polygon>,
+    pub generation: u32,
+
+    pub heatmap: Heatmap,
+    pub show_trails: bool,
+    pub show_heatmap: bool,
 }
 
 impl Entities {
     pub fn new() -> Entities {
+        Entities::with_road(Road::load())
+    }
+
+    pub fn with_road(road: Road) -> Entities {
         let mut cars = Vec::new();
-        let road = Road::load();
-        let player = Player::new();
         let finish_line = FinishLine::new(&road);
 
         let start_origin = finish_line.start.center();
         let start_angle = finish_line.get_start_angle();
+
+        // players start side by side just ahead of the AI cars stacked on the start line, anywhere
+        // further away may be off the track, and clear of each other so nobody starts in a crash
+        let body = Car::default();
+        let (ahead, apart) = (body.height + 20.0, (body.width + 10.0) / 2.0);
+        let players: Vec<Player> = vec![(-apart, KeyBindings::wasd()), (apart, KeyBindings::arrows())]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (offset, bindings))| {
+                let position = Point::new(ahead, offset).transform(start_origin.x, start_origin.y, start_angle);
+                let player = Player::new(position.x, position.y, bindings, i as u32);
+
+                {
+                    let mut car = player.car.borrow_mut();
+                    car.angle = start_angle;
+                    car.update_geometry();
+                    car.reset_checkpoint();
+                }
+
+                player
+            })
+            .collect();
+        players[1].car.borrow_mut().set_color("crimson");
         for _ in 0..10 {
             let mut car = Car::new_at(start_origin.x, start_origin.y);
             car.turn(start_angle);
+            car.update_geometry();
+            car.reset_checkpoint();
             cars.push(car)
         }
 
+        let heatmap = Heatmap::new(&road, HEATMAP_CELL_SIZE);
+
         Entities {
             cars: Rc::new(RefCell::new(cars)),
-            player: Some(player),
+            players,
+            fleet: Fleet::new(7, 200.0, std::f64::consts::PI),
             road,
-            finish_line
+            finish_line,
+            obstacles: vec![],
+            generation: 1,
+
+            heatmap,
+            show_trails: false,
+            show_heatmap: false,
+        }
+    }
+
+    // the AI cars and the fleet read through the same rig their network's input layer was sized for
+    pub fn with_sensor_rig(mut self, rig: &SensorRig) -> Entities {
+        self.cars.borrow_mut().iter_mut().for_each(|car| car.set_sensor_rig(rig.clone()));
+        self.fleet.set_rig(rig.clone());
+        self
+    }
+
+    pub fn best_car(&self) -> Option<usize> {
+        let cars = self.cars.borrow();
+
+        cars.iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| {
+                let a_key = (!a.has_collided, self.road.progress(&Point::new(a.x, a.y)));
+                let b_key = (!b.has_collided, self.road.progress(&Point::new(b.x, b.y)));
+                a_key.partial_cmp(&b_key).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|(index, _)| index)
+    }
+
+    pub fn next_generation(&mut self) {
+        let start_origin = self.finish_line.start.center();
+        let start_angle = self.finish_line.get_start_angle();
+
+        for car in self.cars.borrow_mut().iter_mut() {
+            car.x = start_origin.x;
+            car.y = start_origin.y;
+            car.angle = start_angle;
+            car.speed = 0.0;
+            car.has_collided = false;
+            car.impact = None;
+            car.crashes = 0;
+            car.crash_point = None;
+            car.clear_ticks = CRASH_CLEAR_TICKS;
+            car.controls.reset();
+            car.physics.reset();
+            car.update_geometry();
+            car.reset_trail();
+            car.reset_checkpoint();
+        }
+
+        let fleet_size = self.fleet.len();
+        if fleet_size > 0 {
+            self.fleet.clear();
+            self.spawn_fleet(fleet_size);
+        }
+
+        self.generation += 1;
+    }
+
+    pub fn set_collision_response(&mut self, response: CollisionResponse) {
+        self.cars.borrow_mut().iter_mut().for_each(|car| car.response = response);
+    }
+
+    // every car gets its own stream derived from the seed so runs are reproducible
+    pub fn set_sensor_noise(&mut self, noise: Option<SensorNoise>) {
+        for (i, car) in self.cars.borrow_mut().iter_mut().enumerate() {
+            let noise = noise.map(|n| SensorNoise { seed: n.seed.wrapping_add(i as u64), ..n });
+            car.sensors.set_noise(noise);
         }
     }
 
+    pub fn spawn_fleet(&mut self, count: usize) {
+        let start_origin = self.finish_line.start.center();
+        let start_angle = self.finish_line.get_start_angle();
+
+        self.fleet.spawn(count, start_origin.x, start_origin.y, start_angle);
+    }
+
+    pub fn rebuild_track(&mut self) {
+        self.road.construct();
+        self.finish_line = FinishLine::new(&self.road);
+        self.heatmap.reset(&self.road);
+    }
+
+    pub fn hitboxes(&self) -> Vec<Polygon> {
+        let mut hitboxes: Vec<Polygon> = self.cars.borrow().iter().map(|c| c.hitbox.clone()).collect();
+        hitboxes.extend(self.players.iter().map(|p| p.car.borrow().hitbox.clone()));
+
+        hitboxes
+    }
+
     pub fn update(&mut self) {
-        self.cars.borrow_mut().iter_mut().for_each(|c| c.update(&self.road));
-        if let Some(player) = &self.player {
-            player.update(&self.road);
+        // hitboxes from the previous tick, so every car senses the same snapshot
+        let hitboxes = self.hitboxes();
+        let targets = SensorTargets::new(&hitboxes, &self.obstacles);
+        let car_count = self.cars.borrow().len();
+
+        for (i, car) in self.cars.borrow_mut().iter_mut().enumerate() {
+            let crashes = car.crashes;
+            car.update_with(&self.road, &targets.excluding(i));
+
+            // crashes also count collisions the car bounced, slid or respawned out of
+            if let Some(point) = car.crash_point.filter(|_| car.crashes > crashes) {
+                self.heatmap.record(&point);
+            }
+        }
+
+        let was_collided = self.fleet.collided.clone();
+        self.fleet.update(&self.road);
+        for (i, was_collided) in was_collided.into_iter().enumerate() {
+            if self.fleet.collided[i] && !was_collided {
+                self.heatmap.record(&Point::new(self.fleet.xs[i], self.fleet.ys[i]));
+            }
+        }
+
+        for (i, player) in self.players.iter().enumerate() {
+            let crashes = player.car.borrow().crashes;
+            player.update(&self.road, &targets.excluding(car_count + i));
+
+            let car = player.car.borrow();
+            if let Some(point) = car.crash_point.filter(|_| car.crashes > crashes) {
+                self.heatmap.record(&point);
+            }
         }
         // todo!(); 
         
         // self.check_collisions();
     }
 
-    pub fn draw(&self, context: &web_sys::CanvasRenderingContext2d) {
+    pub fn draw(&self, context: &dyn Renderer) {
         self.road.draw(context);
         self.finish_line.draw(context);  
+        self.obstacles.draw(context);
+        if self.show_heatmap { self.heatmap.draw(context); }
 
-        self.cars.borrow_mut().draw(context);
-        if let Some(player) = &self.player {
-            player.draw(context);
+        if self.show_trails {
+            self.cars.borrow().iter().for_each(|c| c.draw_trail(context, "blue"));
+            self.players.iter().for_each(|p| p.car.borrow().draw_trail(context, "orange"));
         }
+
+        self.fleet.draw(context);
+        self.cars.borrow_mut().draw(context);
+        self.players.draw(context);
+    }
+
+    pub fn to_svg(&self) -> String {
+        snapshot::render_svg(&self.road, snapshot::SNAPSHOT_PADDING, |renderer| self.draw(renderer))
     }
 
     // pub fn check_collisions(&mut self) {
@@ -60,7 +223,7 @@ impl Entities {
     //         }
     //     });
     //
-    //     if let Some(player) = &mut self.player {
+    //     for player in self.players.iter() {
     //         if player.car.borrow_mut().hitbox.intersects(&self.road.hitbox) {
     //             player.car.borrow_mut().collide();
     //             console_log!("intersecting");
@@ -69,3 +232,65 @@ impl Entities {
     // }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::{HitKind, SensorInput};
+
+    #[test]
+    fn default_world_renders_to_svg() {
+        let entities = Entities::with_road(Road::load());
+        let svg = entities.to_svg();
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("<path"));
+    }
+
+    #[test]
+    fn world_updates_without_a_browser() {
+        let mut entities = Entities::with_road(Road::load());
+        entities.players[0].car.borrow_mut().controls.forward = true;
+
+        for _ in 0..60 {
+            entities.update();
+        }
+
+        let start = entities.finish_line.start.center();
+        let car = entities.players[0].car.borrow();
+        assert!(Point::new(car.x, car.y).distance(&start) > 50.0);
+    }
+
+    #[test]
+    fn fleet_readings_follow_the_shared_rig() {
+        let rig = SensorRig::new(5, 200.0, std::f64::consts::PI).with_kind_inputs().with_input(SensorInput::Speed);
+        let mut entities = Entities::with_road(Road::load()).with_sensor_rig(&rig);
+        entities.spawn_fleet(3);
+        entities.fleet.controls[0].forward = true;
+        entities.update();
+
+        assert_eq!(entities.fleet.readings.len(), 3 * rig.input_count());
+        assert_eq!(entities.cars.borrow()[0].sensors.inputs().len(), rig.input_count());
+
+        let readings = entities.fleet.readings_of(0);
+        for r in 0..5 {
+            let kind = if readings[r] > 0.0 { HitKind::Road.input() } else { 0.0 };
+            assert_eq!(readings[5 + r], kind);
+        }
+        assert!(readings[10] > 0.0);
+    }
+
+    #[test]
+    fn players_start_clear_of_each_other_and_the_ai_cars() {
+        let entities = Entities::with_road(Road::load());
+        let players: Vec<Polygon> = entities.players.iter().map(|p| p.car.borrow().hitbox.clone()).collect();
+        let ai = entities.cars.borrow()[0].hitbox.clone();
+
+        assert!(!players[0].overlaps_convex(&players[1]));
+        for player in players.iter() {
+            assert!(!player.overlaps_convex(&ai));
+            assert!(!entities.road.is_off_track(&player.points));
+            assert!(!entities.road.grid.intersects_polygon(player));
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{geo::*, Road, Drawable};
+use crate::{geo::*, Road, Drawable, Renderer};
 
 pub struct FinishLine {
     pub start: Polygon,
@@ -65,7 +65,7 @@ impl FinishLine {
 }
 
 impl Drawable for FinishLine {
-    fn draw(&self, context: &web_sys::CanvasRenderingContext2d) {
+    fn draw(&self, context: &dyn Renderer) {
         self.start.draw(context);
         self.end.draw(context);
     }
@@ -0,0 +1,260 @@
+use crate::{geo::Point, helpers, sensors::{HitKind, SensorRig}, Controls, Drawable, Renderer, Road};
+
+pub struct Fleet {
+    pub xs: Vec<f64>,
+    pub ys: Vec<f64>,
+    pub angles: Vec<f64>,
+    pub speeds: Vec<f64>,
+    pub controls: Vec<Controls>,
+    pub collided: Vec<bool>,
+    pub readings: Vec<f64>,
+
+    pub width: f64,
+    pub height: f64,
+    pub max_speed: f64,
+    pub acceleration: f64,
+    pub friction: f64,
+
+    pub rig: SensorRig,
+
+    candidates: Vec<usize>,
+}
+
+impl Fleet {
+    pub fn new(ray_count: u32, ray_length: f64, ray_spread: f64) -> Fleet {
+        Fleet::from_rig(SensorRig::new(ray_count, ray_length, ray_spread).with_body(80.0, 40.0))
+    }
+
+    pub fn from_rig(rig: SensorRig) -> Fleet {
+        Fleet {
+            xs: vec![],
+            ys: vec![],
+            angles: vec![],
+            speeds: vec![],
+            controls: vec![],
+            collided: vec![],
+            readings: vec![],
+
+            width: 40.0,
+            height: 80.0,
+            max_speed: 20.0,
+            acceleration: 0.1,
+            friction: 0.05,
+
+            rig,
+
+            candidates: vec![],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+
+    pub fn ray_count(&self) -> usize {
+        self.rig.ray_count()
+    }
+
+    // readings hold this many values per car, laid out like `Sensors::inputs`
+    pub fn input_count(&self) -> usize {
+        self.rig.input_count()
+    }
+
+    pub fn set_rig(&mut self, rig: SensorRig) {
+        self.rig = rig;
+        self.readings = vec![0.0; self.len() * self.input_count()];
+    }
+
+    pub fn spawn(&mut self, count: usize, x: f64, y: f64, angle: f64) {
+        let total = self.len() + count;
+
+        self.xs.resize(total, x);
+        self.ys.resize(total, y);
+        self.angles.resize(total, angle);
+        self.speeds.resize(total, 0.0);
+        self.controls.resize_with(total, Controls::new);
+        self.collided.resize(total, false);
+        self.readings.resize(total * self.input_count(), 0.0);
+    }
+
+    pub fn clear(&mut self) {
+        self.xs.clear();
+        self.ys.clear();
+        self.angles.clear();
+        self.speeds.clear();
+        self.controls.clear();
+        self.collided.clear();
+        self.readings.clear();
+    }
+
+    pub fn alive(&self) -> usize {
+        self.collided.iter().filter(|c| !**c).count()
+    }
+
+    // `controls` holds steering, throttle and braking for each car in turn
+    pub fn set_controls(&mut self, controls: &[f64]) {
+        for (car, values) in self.controls.iter_mut().zip(controls.chunks_exact(3)) {
+            car.update_analog(values[0], values[1], values[2]);
+        }
+    }
+
+    pub fn readings_of(&self, index: usize) -> &[f64] {
+        let count = self.input_count();
+        &self.readings[index * count..(index + 1) * count]
+    }
+
+    pub fn corners(&self, index: usize) -> [Point; 4] {
+        let (x, y, angle) = (self.xs[index], self.ys[index], self.angles[index]);
+        let half_length = self.height / 2.0;
+        let half_width = self.width / 2.0;
+
+        [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(u, v)| {
+            Point::new(u * half_length, v * half_width).transform(x, y, angle)
+        })
+    }
+
+    pub fn update(&mut self, road: &Road) {
+        for i in 0..self.len() {
+            if self.collided[i] { continue }
+
+            let previous = (self.xs[i], self.ys[i], self.angles[i]);
+            let previous_corners = self.corners(i);
+
+            self.apply_controls(i);
+            self.move_coords(i);
+
+            let corners = self.corners(i);
+            if let Some(hit) = road.grid.sweep_outline(&previous_corners, &corners, &mut self.candidates) {
+                self.xs[i] = helpers::lerpf(previous.0, self.xs[i], hit.time);
+                self.ys[i] = helpers::lerpf(previous.1, self.ys[i], hit.time);
+                self.angles[i] = helpers::lerpf(previous.2, self.angles[i], hit.time);
+                self.collided[i] = true;
+            } else if road.grid.crossed_line(&corners, &self.candidates).is_some() {
+                // the sweep already gathered every line around both poses
+                self.collided[i] = true;
+            } else if self.candidates.is_empty() && road.is_off_track(&corners) {
+                // without any edge nearby the car is either well inside or well outside the road
+                self.collided[i] = true;
+            }
+
+            self.sense(i, road);
+
+            if self.collided[i] {
+                self.speeds[i] = 0.0;
+                self.controls[i].reset();
+            }
+        }
+    }
+
+    fn apply_controls(&mut self, i: usize) {
+        let controls = &self.controls[i];
+        let mut speed = self.speeds[i];
+        let mut angle = self.angles[i];
+
+        let braking = controls.brake_input();
+
+        speed += self.acceleration * controls.throttle_input();
+        if controls.backward { speed -= self.acceleration; }
+
+        let turn = if speed >= 0.0 { 0.1 } else { -0.1 };
+        angle += turn * controls.steering_input();
+
+        if braking > 0.0 {
+            let new_speed = (speed.abs() - self.acceleration * 2.5 * braking).max(0.0);
+            speed = if speed >= 0.0 { new_speed } else { -new_speed };
+        }
+
+        speed = if speed > self.max_speed {
+            self.max_speed
+        } else if speed < -self.max_speed {
+            -self.max_speed
+        } else if speed.abs() < self.friction.abs() {
+            0.0
+        } else {
+            speed - self.friction * speed.signum()
+        };
+
+        self.speeds[i] = speed;
+        self.angles[i] = angle;
+    }
+
+    fn move_coords(&mut self, i: usize) {
+        let speed = self.speeds[i];
+        if speed == 0.0 { return; }
+
+        self.xs[i] += speed * self.angles[i].cos();
+        self.ys[i] += speed * self.angles[i].sin();
+    }
+
+    fn sense(&mut self, i: usize, road: &Road) {
+        let count = self.ray_count();
+        let start = i * self.input_count();
+        let finish = road.grid.lines.len().wrapping_sub(1);
+
+        for r in 0..count {
+            let ray = self.rig.ray(r, self.xs[i], self.ys[i], self.angles[i]);
+
+            let mut offset = None::<f64>;
+            road.grid.query_into(&ray.start, &ray.end, &mut self.candidates);
+
+            for &index in self.candidates.iter() {
+                // skip the last line which is the finish line
+                if index == finish { continue }
+
+                if let Some(intersection) = ray.get_intersection(&road.grid.lines[index]) {
+                    if intersection.intersects && offset.is_none_or(|o| intersection.offset < o) {
+                        offset = Some(intersection.offset);
+                    }
+                }
+            }
+
+            // same convention as Sensor::input, 0 when nothing is hit
+            self.readings[start + r] = offset.map_or(0.0, |o| 1.0 - o);
+            // the fleet only ever senses the road
+            if self.rig.kind_inputs {
+                self.readings[start + count + r] = offset.map_or(0.0, |_| HitKind::Road.input());
+            }
+        }
+
+        if self.rig.inputs.is_empty() { return }
+
+        let extra = start + if self.rig.kind_inputs { 2 * count } else { count };
+        let position = road.locate(&Point::new(self.xs[i], self.ys[i]));
+        let speed_ratio = self.speeds[i] / self.max_speed;
+
+        for (k, input) in self.rig.inputs.iter().enumerate() {
+            self.readings[extra + k] = input.read(road, position.as_ref(), self.angles[i], speed_ratio);
+        }
+    }
+}
+
+impl Drawable for Fleet {
+    fn draw(&self, context: &dyn Renderer) {
+        if self.is_empty() { return }
+
+        context.save();
+        context.set_stroke_color("black");
+
+        for (color, collided) in [("gray", true), ("blue", false)] {
+            context.set_fill_color(color);
+            context.begin_path();
+
+            for i in (0..self.len()).filter(|i| self.collided[*i] == collided) {
+                let corners = self.corners(i);
+
+                context.move_to(corners[0].x, corners[0].y);
+                corners.iter().skip(1).for_each(|c| context.line_to(c.x, c.y));
+                context.close_path();
+            }
+
+            context.fill();
+            context.stroke();
+        }
+
+        context.restore();
+    }
+}
@@ -0,0 +1,91 @@
+use wasm_bindgen::JsCast;
+
+use crate::{js, Controls, ControlMode};
+
+pub struct GamepadConfig {
+    pub index: u32,
+    pub deadzone: f64,
+    pub trigger_deadzone: f64,
+
+    pub steering_axis: u32,
+    pub throttle_button: u32,
+    pub brake_button: u32,
+    pub reverse_button: u32,
+}
+
+pub struct GamepadState {
+    pub steering: f64,
+    pub throttle: f64,
+    pub braking: f64,
+    pub reverse: bool,
+}
+
+impl GamepadConfig {
+    pub fn new(index: u32) -> GamepadConfig {
+        // defaults follow the "standard" gamepad mapping: left stick, RT, LT, B
+        GamepadConfig {
+            index,
+            deadzone: 0.15,
+            trigger_deadzone: 0.05,
+
+            steering_axis: 0,
+            throttle_button: 7,
+            brake_button: 6,
+            reverse_button: 1,
+        }
+    }
+
+    pub fn apply_deadzone(value: f64, deadzone: f64) -> f64 {
+        if value.abs() <= deadzone {
+            return 0.0;
+        }
+
+        value.signum() * (value.abs() - deadzone) / (1.0 - deadzone)
+    }
+
+    pub fn read(&self) -> Option<GamepadState> {
+        let gamepad = js::gamepad(self.index)?;
+        let axes = gamepad.axes();
+        let buttons = gamepad.buttons();
+
+        let button = |index: u32| {
+            buttons
+                .get(index)
+                .dyn_into::<web_sys::GamepadButton>()
+                .map(|b| b.value())
+                .unwrap_or(0.0)
+        };
+
+        let steering = axes.get(self.steering_axis).as_f64().unwrap_or(0.0);
+
+        Some(GamepadState {
+            steering: GamepadConfig::apply_deadzone(steering, self.deadzone),
+            throttle: GamepadConfig::apply_deadzone(button(self.throttle_button), self.trigger_deadzone),
+            braking: GamepadConfig::apply_deadzone(button(self.brake_button), self.trigger_deadzone),
+            reverse: button(self.reverse_button) > 0.5,
+        })
+    }
+
+    pub fn poll(&self, controls: &mut Controls) {
+        let state = match self.read() {
+            Some(state) if state.is_active() => state,
+            // hand control back to the keyboard once the pad goes idle
+            _ => {
+                if controls.mode == ControlMode::Analog {
+                    controls.mode = ControlMode::Digital;
+                    controls.backward = false;
+                }
+                return;
+            }
+        };
+
+        controls.update_analog(state.steering, state.throttle, state.braking);
+        controls.backward = state.reverse;
+    }
+}
+
+impl GamepadState {
+    pub fn is_active(&self) -> bool {
+        self.steering != 0.0 || self.throttle > 0.0 || self.braking > 0.0 || self.reverse
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{geo::Point, Drawable, Renderer, Road};
+
+pub const HEATMAP_CELL_SIZE: f64 = 20.0;
+
+pub struct Heatmap {
+    pub origin: Point,
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+    pub counts: Vec<u32>,
+    pub max_count: u32,
+}
+
+impl Heatmap {
+    pub fn new(road: &Road, cell_size: f64) -> Heatmap {
+        let mut heatmap = Heatmap {
+            origin: Point::default(),
+            cell_size,
+            columns: 0,
+            rows: 0,
+            counts: vec![],
+            max_count: 0,
+        };
+
+        heatmap.reset(road);
+        heatmap
+    }
+
+    pub fn reset(&mut self, road: &Road) {
+        self.counts.clear();
+        self.columns = 0;
+        self.rows = 0;
+        self.max_count = 0;
+
+        let Some((min, max)) = road.hitbox.bounds() else { return };
+
+        self.origin = min;
+        self.columns = ((max.x - min.x) / self.cell_size).floor() as usize + 1;
+        self.rows = ((max.y - min.y) / self.cell_size).floor() as usize + 1;
+        self.counts = vec![0; self.columns * self.rows];
+    }
+
+    fn cell_index(&self, point: &Point) -> Option<usize> {
+        let column = ((point.x - self.origin.x) / self.cell_size).floor();
+        let row = ((point.y - self.origin.y) / self.cell_size).floor();
+
+        if column < 0.0 || row < 0.0 || column as usize >= self.columns || row as usize >= self.rows {
+            return None;
+        }
+
+        Some(row as usize * self.columns + column as usize)
+    }
+
+    pub fn record(&mut self, point: &Point) {
+        let Some(index) = self.cell_index(point) else { return };
+
+        self.counts[index] += 1;
+        self.max_count = self.max_count.max(self.counts[index]);
+    }
+
+    pub fn count_at(&self, point: &Point) -> u32 {
+        self.cell_index(point).map_or(0, |index| self.counts[index])
+    }
+}
+
+impl Drawable for Heatmap {
+    fn draw(&self, context: &dyn Renderer) {
+        if self.max_count == 0 { return }
+
+        context.save();
+        context.set_fill_color("red");
+
+        for (index, count) in self.counts.iter().enumerate() {
+            if *count == 0 { continue }
+
+            let column = index % self.columns;
+            let row = index / self.columns;
+
+            context.set_global_alpha(0.15 + 0.6 * *count as f64 / self.max_count as f64);
+            context.fill_rect(
+                self.origin.x + column as f64 * self.cell_size,
+                self.origin.y + row as f64 * self.cell_size,
+                self.cell_size,
+                self.cell_size,
+            );
+        }
+
+        context.restore();
+    }
+}
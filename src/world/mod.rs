@@ -4,8 +4,19 @@ pub mod road;
 pub mod finish;
 pub mod player;
 pub mod sensors;
+pub mod fleet;
+pub mod physics;
+pub mod gamepad;
+pub mod bindings;
+pub mod snapshot;
+pub mod heatmap;
+pub mod collision;
 
 pub use car::*;
 pub use entities::*;
 pub use road::*;
 pub use finish::*;
+pub use fleet::*;
+pub use physics::*;
+pub use heatmap::*;
+pub use collision::*;
@@ -0,0 +1,92 @@
+use crate::helpers::clampf;
+
+#[derive(Default)]
+pub enum PhysicsModel {
+    #[default]
+    Arcade,
+    Bicycle(BicycleModel),
+}
+
+impl PhysicsModel {
+    pub fn bicycle(wheelbase: f64) -> PhysicsModel {
+        PhysicsModel::Bicycle(BicycleModel::new(wheelbase))
+    }
+
+    pub fn lateral_speed(&self) -> f64 {
+        match self {
+            PhysicsModel::Arcade => 0.0,
+            PhysicsModel::Bicycle(model) => model.lateral_speed,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        if let PhysicsModel::Bicycle(model) = self {
+            model.reset();
+        }
+    }
+}
+
+pub struct BicycleModel {
+    pub wheelbase: f64,
+    pub max_steering: f64,
+    pub steering_rate: f64,
+    pub steering_falloff: f64,
+    pub grip: f64,
+
+    pub steering: f64,
+    pub lateral_speed: f64,
+    pub yaw_rate: f64,
+}
+
+impl BicycleModel {
+    pub fn new(wheelbase: f64) -> BicycleModel {
+        BicycleModel {
+            wheelbase,
+            max_steering: 0.6,
+            steering_rate: 0.08,
+            steering_falloff: 0.5,
+            grip: 0.6,
+
+            steering: 0.0,
+            lateral_speed: 0.0,
+            yaw_rate: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.steering = 0.0;
+        self.lateral_speed = 0.0;
+        self.yaw_rate = 0.0;
+    }
+
+    pub fn steering_limit(&self, speed: f64, max_speed: f64) -> f64 {
+        let ratio = if max_speed > 0.0 { clampf(speed.abs() / max_speed, 0.0, 1.0) } else { 0.0 };
+        self.max_steering * (1.0 - self.steering_falloff * ratio)
+    }
+
+    /// Moves the wheels towards `input` (in [-1, 1]) and returns the heading change for this tick.
+    /// Rotating the heading leaves the velocity vector behind, whatever the tyres can't absorb
+    /// with `grip` stays as sideways slip, and `speed` is updated to the new forward component.
+    pub fn step(&mut self, input: f64, speed: &mut f64, max_speed: f64) -> f64 {
+        let limit = self.steering_limit(*speed, max_speed);
+        let target = clampf(input, -1.0, 1.0) * limit;
+
+        self.steering += clampf(target - self.steering, -self.steering_rate, self.steering_rate);
+        self.steering = clampf(self.steering, -limit, limit);
+
+        self.yaw_rate = *speed * self.steering.tan() / self.wheelbase;
+
+        let (sin, cos) = self.yaw_rate.sin_cos();
+        let forward = *speed * cos + self.lateral_speed * sin;
+        let lateral = self.lateral_speed * cos - *speed * sin;
+
+        *speed = forward;
+        self.lateral_speed = lateral - clampf(lateral, -self.grip, self.grip);
+
+        self.yaw_rate
+    }
+
+    pub fn is_drifting(&self) -> bool {
+        self.lateral_speed != 0.0
+    }
+}
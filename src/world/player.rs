@@ -1,75 +1,54 @@
 use std::{rc::Rc, cell::RefCell};
 
-use wasm_bindgen::prelude::*;
-use web_sys::window;
-
-use crate::{car::Car, Drawable, Road};
+use crate::{bindings::KeyBindings, car::Car, gamepad::GamepadConfig, input::Input, sensors::SensorTargets, Drawable, Renderer, Road};
 
 pub struct Player {
     pub car: Rc<RefCell<Car>>,
+    pub bindings: Rc<RefCell<KeyBindings>>,
+    pub gamepad: GamepadConfig,
+    pub input: Input,
 }
 
 impl Player {
-    pub fn new() -> Player {
+    pub fn new(x: f64, y: f64, bindings: KeyBindings, gamepad_index: u32) -> Player {
         let player = Player {
-            car: Rc::new(RefCell::new(Car::new_at(200.0, 200.0))),
+            car: Rc::new(RefCell::new(Car::new_at(x, y))),
+            bindings: Rc::new(RefCell::new(bindings)),
+            gamepad: GamepadConfig::new(gamepad_index),
+            input: Input::new(),
         };
-        player.add_controls_listeners();
         player.car.borrow_mut().speed = 0.0;
 
         player
     }
 
-    pub fn update(&self, road: &Road) {
-        self.car.borrow_mut().update(road);
+    // gamepads are polled by the game beforehand, so updating needs no browser
+    pub fn update(&self, road: &Road, targets: &SensorTargets) {
+        self.car.borrow_mut().update_with(road, targets);
     }
 
-    pub fn add_controls_listeners(&self) {
-        {
-            let car = self.car.clone();
-            let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::KeyboardEvent| {
-                let mut car = car.borrow_mut();
-
-                match event.key().as_str() {
-                    "ArrowUp" | "w" => car.controls.forward = true,
-                    "ArrowDown" | "s" => car.controls.backward = true,
-                    "ArrowLeft" | "a" => car.controls.left = true,
-                    "ArrowRight" | "d" => car.controls.right = true,
-                    " " => car.controls.brake = true,
-                    _ => {}
-                }
-            });
-            if let Some(window) = window() {
-                let _ = window.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref());
-            }
-            closure.forget();
-        }
-
-        {
+    // kept out of `new` so players can be created where there is no window to listen on
+    pub fn add_controls_listeners(&mut self) {
+        for (event_name, pressed) in [("keydown", true), ("keyup", false)] {
             let car = self.car.clone();
-            let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::KeyboardEvent| {
-                let mut car = car.borrow_mut();
+            let bindings = self.bindings.clone();
 
-                match event.key().as_str() {
-                    "ArrowUp" | "w" => car.controls.forward = false,
-                    "ArrowDown" | "s" => car.controls.backward = false,
-                    "ArrowLeft" | "a" => car.controls.left = false,
-                    "ArrowRight" | "d" => car.controls.right = false,
-                    " " => car.controls.brake = false,
-                    _ => {}
+            self.input.listen_window(event_name, move |event: web_sys::KeyboardEvent| {
+                if let Some(action) = bindings.borrow().action(event.key().as_str()) {
+                    action.apply(&mut car.borrow_mut().controls, pressed);
                 }
             });
-            if let Some(window) = window() {
-                let _ = window.add_event_listener_with_callback("keyup", closure.as_ref().unchecked_ref());
-            }
-            closure.forget();
         }
+    }
 
+    pub fn remove_controls_listeners(&mut self) {
+        self.input.clear();
+        self.car.borrow_mut().controls.reset();
     }
 }
 
 impl Drawable for Player {
-    fn draw(&self, context: &web_sys::CanvasRenderingContext2d) {
+    fn draw(&self, context: &dyn Renderer) {
         self.car.borrow().draw(context);
     }
 }
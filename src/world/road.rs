@@ -1,12 +1,25 @@
-use wasm_bindgen::JsValue;
+use crate::{geo::*, grid::SpatialGrid, helpers::{lerpf, wrap_angle}, Drawable, Renderer};
 
-use crate::{geo::*, js, Drawable};
+pub const ROAD_GRID_CELL_SIZE: f64 = 100.0;
 
 
 pub struct Road {
     pub lines: Vec<RoadLine>,
     pub points: Vec<RoadPoint>,
     pub hitbox: Polygon,
+    pub grid: SpatialGrid,
+}
+
+pub struct TrackPosition {
+    pub index: usize,
+    pub t: f64,
+    pub point: Point,
+    pub distance: f64,
+    pub progress: f64,
+    pub direction: f64,
+    // signed distance from the centerline, positive on the right hand side of the direction of travel
+    pub lateral_offset: f64,
+    pub width: f64,
 }
 
 pub struct RoadPoint {
@@ -48,16 +61,16 @@ impl RoadLine {
         RoadLine::new(line, a.width, b.width)
     }
 
-    pub fn draw(&self, context: &web_sys::CanvasRenderingContext2d) {
+    pub fn draw(&self, context: &dyn Renderer) {
         context.save();
         context.set_line_width(5.0);
-        context.set_fill_style(&JsValue::from_str("white"));
+        context.set_fill_color("white");
 
-        context.set_stroke_style(&JsValue::from_str("black"));
+        context.set_stroke_color("black");
         self.left.draw(context);
         self.right.draw(context);
 
-        context.set_stroke_style(&JsValue::from_str("gray"));
+        context.set_stroke_color("gray");
         self.line.draw(context);
 
         context.fill();
@@ -71,7 +84,8 @@ impl Road {
         Road {
             lines: vec![],
             points: vec![],
-            hitbox: Polygon::new(vec![], "white".to_string())
+            hitbox: Polygon::new(vec![], "white".to_string()),
+            grid: SpatialGrid::new(vec![], ROAD_GRID_CELL_SIZE),
         }
     }
 
@@ -129,10 +143,82 @@ impl Road {
         self.lines.iter().rev().for_each(|l| hitbox_points.push(l.right.start));
 
         self.hitbox.points = hitbox_points;
+        self.grid.build(self.hitbox.lines());
+    }
+
+    pub fn describe(&self) -> String {
+        self.points.iter().map(|p| format!("{:.2} {:.2} {:.2}", p.point.x, p.point.y, p.width)).collect::<Vec<String>>().join(", \n")
+    }
+
+    pub fn length(&self) -> f64 {
+        self.lines.iter().map(|l| l.line.length()).sum()
+    }
+
+    pub fn locate(&self, point: &Point) -> Option<TrackPosition> {
+        let mut travelled = 0.0;
+        let mut closest: Option<TrackPosition> = None;
+
+        for (index, road_line) in self.lines.iter().enumerate() {
+            let line = &road_line.line;
+            let t = line.project(point);
+            let closest_point = line.point_at(t);
+            let distance = closest_point.distance(point);
+
+            if closest.as_ref().is_none_or(|c| distance < c.distance) {
+                let direction = line.angle();
+                let lateral_offset = (point.y - closest_point.y) * direction.cos() - (point.x - closest_point.x) * direction.sin();
+
+                closest = Some(TrackPosition {
+                    index,
+                    t,
+                    point: closest_point,
+                    distance,
+                    progress: travelled + t * line.length(),
+                    direction,
+                    lateral_offset,
+                    width: lerpf(road_line.start_width, road_line.end_width, t),
+                });
+            }
+
+            travelled += line.length();
+        }
 
-        let points_str = self.points.iter().map(|p| format!("{:.2} {:.2} {:.2}", p.point.x, p.point.y, p.width)).collect::<Vec<String>>().join(", \n");
+        closest
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        self.hitbox.contains_point(point)
+    }
+
+    // a road that is still being drawn has no area yet, so nothing counts as off track
+    pub fn is_off_track(&self, points: &[Point]) -> bool {
+        self.hitbox.points.len() >= 3 && points.iter().any(|p| !self.contains(p))
+    }
+
+    pub fn progress(&self, point: &Point) -> f64 {
+        self.locate(point).map_or(0.0, |p| p.progress)
+    }
+
+    // direction of the centerline at a distance along the track, wrapping around the loop
+    pub fn direction_at(&self, progress: f64) -> Option<f64> {
+        let length = self.length();
+        if length <= 0.0 { return None }
+
+        let mut remaining = progress.rem_euclid(length);
+        for road_line in self.lines.iter() {
+            let line_length = road_line.line.length();
+            if remaining <= line_length { return Some(road_line.line.angle()) }
+
+            remaining -= line_length;
+        }
+
+        self.lines.last().map(|l| l.line.angle())
+    }
 
-        js::get_element_by_id("rustOutput").set_text_content(Some(&points_str));
+    // change of the centerline direction between here and `lookahead` units further along
+    pub fn curvature_ahead(&self, position: &TrackPosition, lookahead: f64) -> f64 {
+        self.direction_at(position.progress + lookahead)
+            .map_or(0.0, |ahead| wrap_angle(ahead - position.direction))
     }
 
     pub fn merge(lines: &mut [RoadLine], index: usize) {
@@ -154,7 +240,7 @@ impl Road {
 }
 
 impl Drawable for Road {
-    fn draw(&self, context: &web_sys::CanvasRenderingContext2d) {
+    fn draw(&self, context: &dyn Renderer) {
         self.hitbox.draw(context);
         self.lines.iter().for_each(|l| l.draw(context));
     }
@@ -181,7 +267,8 @@ impl Road {
             RoadPoint { point: Point::new(89.0, 510.0), width: 43.34 }, 
         ];
 
-        let mut road = Road { points, lines: vec![], hitbox: Polygon::new(vec![], "white".to_string()) };
+        let mut road = Road::new();
+        road.points = points;
         road.construct();
 
         road
@@ -1,11 +1,254 @@
-use wasm_bindgen::JsValue;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use crate::{geo::{Line, Point, Polygon}, helpers, Drawable, Road};
+use crate::{geo::{Line, Point, Polygon}, grid::SpatialGrid, helpers, Drawable, Renderer, Road, TrackPosition};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SensorInput {
+    Speed,
+    HeadingError,
+    LateralOffset,
+    Curvature { lookahead: f64 },
+}
+
+impl SensorInput {
+    // every input is normalized to roughly [-1, 1]
+    pub fn read(&self, road: &Road, position: Option<&TrackPosition>, angle: f64, speed_ratio: f64) -> f64 {
+        let Some(position) = position else {
+            return if *self == SensorInput::Speed { speed_ratio } else { 0.0 };
+        };
+
+        match self {
+            SensorInput::Speed => speed_ratio,
+            SensorInput::HeadingError => helpers::wrap_angle(angle - position.direction) / std::f64::consts::PI,
+            SensorInput::LateralOffset => position.lateral_offset / position.width.max(1.0),
+            SensorInput::Curvature { lookahead } => road.curvature_ahead(position, *lookahead) / std::f64::consts::PI,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SensorRig {
+    pub length: f64,
+    pub angles: Vec<f64>,
+    // ray origins in car-local coordinates, with +x pointing forward
+    pub mounts: Vec<Point>,
+    pub inputs: Vec<SensorInput>,
+    pub kind_inputs: bool,
+}
+
+impl SensorRig {
+    pub fn new(count: u32, length: f64, spread: f64) -> SensorRig {
+        SensorRig::with_angles(length, SensorRig::fan(count, spread, 0.0))
+    }
+
+    pub fn with_angles(length: f64, angles: Vec<f64>) -> SensorRig {
+        let mounts = vec![Point::default(); angles.len()];
+
+        SensorRig { length, angles, mounts, inputs: vec![], kind_inputs: false }
+    }
+
+    pub fn with_input(mut self, input: SensorInput) -> SensorRig {
+        self.inputs.push(input);
+        self
+    }
+
+    pub fn with_mount(mut self, x: f64, y: f64) -> SensorRig {
+        self.mounts.iter_mut().for_each(|m| *m = Point::new(x, y));
+        self
+    }
+
+    // moves every ray's origin onto the outline of a car body centered on the car,
+    // so a reading measures the clearance between the body and the obstacle
+    pub fn with_body(mut self, length: f64, width: f64) -> SensorRig {
+        let (half_length, half_width) = (length / 2.0, width / 2.0);
+
+        self.mounts = self.angles.iter().map(|angle| {
+            let (sin, cos) = angle.sin_cos();
+            let to_front = if cos.abs() > f64::EPSILON { half_length / cos.abs() } else { f64::MAX };
+            let to_side = if sin.abs() > f64::EPSILON { half_width / sin.abs() } else { f64::MAX };
+            let distance = to_front.min(to_side);
+
+            Point::new(cos * distance, sin * distance)
+        }).collect();
+
+        self
+    }
+
+    pub fn with_rear_rays(mut self, count: u32, spread: f64) -> SensorRig {
+        self.angles.extend(SensorRig::fan(count, spread, std::f64::consts::PI));
+        self.mounts.resize(self.angles.len(), Point::default());
+        self
+    }
+
+    pub fn ray_count(&self) -> usize {
+        self.angles.len()
+    }
+
+    // adds one input per ray telling the network what kind of object the ray hit
+    pub fn with_kind_inputs(mut self) -> SensorRig {
+        self.kind_inputs = true;
+        self
+    }
+
+    pub fn input_count(&self) -> usize {
+        let kinds = if self.kind_inputs { self.ray_count() } else { 0 };
+
+        self.ray_count() + kinds + self.inputs.len()
+    }
+
+    pub fn reach(&self) -> f64 {
+        self.length + self.mounts.iter().map(|m| m.distance(&Point::default())).fold(0.0, f64::max)
+    }
+
+    pub fn ray(&self, index: usize, x: f64, y: f64, angle: f64) -> Line {
+        let start = self.mounts[index].transform(x, y, angle);
+        let angle = angle + self.angles[index];
+
+        Line::new(start, Point::new(start.x + angle.cos() * self.length, start.y + angle.sin() * self.length))
+    }
+
+    fn fan(count: u32, spread: f64, center: f64) -> Vec<f64> {
+        (0..count).map(|i| {
+            let t = if count > 1 { i as f64 / (count - 1) as f64 } else { 0.5 };
+            center + helpers::lerpf(-spread / 2.0, spread / 2.0, t)
+        }).collect()
+    }
+}
+
+impl Default for SensorRig {
+    fn default() -> SensorRig {
+        SensorRig::new(7, 200.0, std::f64::consts::PI)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SensorNoise {
+    // standard deviation of the gaussian noise, as a fraction of the ray length
+    pub std_dev: f64,
+    // step the offset is rounded to, 0 disables quantization
+    pub quantization: f64,
+    // probability of a ray reporting no hit at all
+    pub dropout: f64,
+    pub seed: u64,
+}
+
+impl SensorNoise {
+    pub fn new(std_dev: f64, quantization: f64, dropout: f64, seed: u64) -> SensorNoise {
+        SensorNoise { std_dev, quantization, dropout, seed }
+    }
+
+    pub fn apply(&self, sensor: &mut Sensor, rng: &mut StdRng) {
+        let Some(reading) = sensor.reading.as_mut() else { return };
+
+        if self.dropout > 0.0 && rng.gen_bool(self.dropout.min(1.0)) {
+            sensor.reading = None;
+            return;
+        }
+
+        let mut offset = reading.offset;
+        if self.std_dev > 0.0 {
+            offset += gaussian(rng) * self.std_dev;
+        }
+        if self.quantization > 0.0 {
+            offset = (offset / self.quantization).round() * self.quantization;
+        }
+        offset = helpers::clampf(offset, 0.0, 1.0);
+
+        reading.offset = offset;
+        reading.distance = offset * sensor.length;
+        reading.point = sensor.ray.point_at(offset);
+    }
+}
+
+// standard normal sample using the Box-Muller transform
+fn gaussian(rng: &mut StdRng) -> f64 {
+    let u: f64 = 1.0 - rng.gen::<f64>();
+    let v: f64 = rng.gen();
+
+    (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HitKind {
+    Road,
+    Obstacle,
+    Car,
+}
+
+impl HitKind {
+    // network input for the kind of object a ray hit, 0 is kept for "no hit"
+    pub fn input(&self) -> f64 {
+        match self {
+            HitKind::Road => 1.0 / 3.0,
+            HitKind::Obstacle => 2.0 / 3.0,
+            HitKind::Car => 1.0,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SensorTargets<'a> {
+    pub cars: &'a [Polygon],
+    pub obstacles: &'a [Polygon],
+    // index into `cars` of the hitbox belonging to the sensing car
+    pub exclude: Option<usize>,
+}
+
+impl<'a> SensorTargets<'a> {
+    pub fn new(cars: &'a [Polygon], obstacles: &'a [Polygon]) -> SensorTargets<'a> {
+        SensorTargets { cars, obstacles, exclude: None }
+    }
+
+    pub fn excluding(&self, index: usize) -> SensorTargets<'a> {
+        SensorTargets { exclude: Some(index), ..*self }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SensorReading {
+    pub distance: f64,
+    // fraction of the ray length at which the hit happened, 0 at the mount point
+    pub offset: f64,
+    pub point: Point,
+    pub kind: HitKind,
+}
+
+impl SensorReading {
+    pub fn closeness(&self) -> f64 {
+        1.0 - self.offset
+    }
+}
 
 pub struct Sensor {
     pub ray: Line,
-    pub reading: f64,
+    pub reading: Option<SensorReading>,
     pub length: f64,
+    pub angle: f64,
+}
+
+impl Sensor {
+    pub fn check_line(&mut self, obstacle: &Line, kind: HitKind) {
+        let Some(intersection) = self.ray.get_intersection(obstacle) else { return };
+        if !intersection.intersects { return }
+
+        if self.reading.is_some_and(|r| r.offset <= intersection.offset) { return }
+
+        self.reading = Some(SensorReading {
+            distance: intersection.offset * self.length,
+            offset: intersection.offset,
+            point: intersection.point,
+            kind,
+        });
+    }
+
+    // network input: 0 when nothing is in range, approaching 1 as the hit gets closer
+    pub fn input(&self) -> f64 {
+        self.reading.map_or(0.0, |r| r.closeness())
+    }
+
+    pub fn kind_input(&self) -> f64 {
+        self.reading.map_or(0.0, |r| r.kind.input())
+    }
 }
 
 pub struct Sensors {
@@ -13,62 +256,139 @@ pub struct Sensors {
     pub x: f64,    
     pub y: f64,
     pub angle: f64,
-    pub spread: f64,
+    pub rig: SensorRig,
+    pub extra_inputs: Vec<f64>,
+    pub noise: Option<SensorNoise>,
+    rng: StdRng,
+    candidates: Vec<usize>,
 }
 
 impl Sensors {
     pub fn new(x: f64, y: f64, count: u32, length: f64, spread: f64, angle: f64) -> Sensors {
-        let mut sensors = Vec::new();
-        
-        for i in 0..count {
-            let angle = angle + helpers::lerpf(-spread / 2.0, spread / 2.0, i as f64 / (count - 1) as f64);
-            let ray = Line::new(Point::new(x, y), Point::new(x + angle.cos() * length, y + angle.sin() * length)); 
+        Sensors::from_rig(SensorRig::new(count, length, spread), x, y, angle)
+    }
 
-            sensors.push(Sensor { ray, reading: 0.0, length });
-        }
+    pub fn from_rig(rig: SensorRig, x: f64, y: f64, angle: f64) -> Sensors {
+        let sensors = rig.angles.iter().map(|&angle| {
+            let ray = Line::new(Point::default(), Point::default());
+            Sensor { ray, reading: None, length: rig.length, angle }
+        }).collect();
 
-        Sensors {
+        let mut sensors = Sensors {
             sensors,
             x, y,
             angle,
-            spread,
+            extra_inputs: vec![0.0; rig.inputs.len()],
+            rig,
+            noise: None,
+            rng: StdRng::seed_from_u64(0),
+            candidates: vec![],
+        };
+
+        sensors.set_pose(x, y, angle);
+        sensors
+    }
+
+    pub fn set_pose(&mut self, x: f64, y: f64, angle: f64) {
+        self.x = x;
+        self.y = y;
+        self.angle = angle;
+
+        for (index, sensor) in self.sensors.iter_mut().enumerate() {
+            sensor.ray = self.rig.ray(index, x, y, angle);
         }
     }
 
-    pub fn update(&mut self, road: &Road) {
+    pub fn update(&mut self, road: &Road, targets: &SensorTargets, body: &Polygon, speed_ratio: f64) {
         self.reset();
 
-        self.check_polygon(&road.hitbox);
+        self.check_grid(&road.grid);
+        self.check_targets(targets, body);
+        self.apply_noise();
+        self.read_inputs(road, speed_ratio);
+    }
+
+    pub fn set_noise(&mut self, noise: Option<SensorNoise>) {
+        if let Some(noise) = noise {
+            self.rng = StdRng::seed_from_u64(noise.seed);
+        }
+        self.noise = noise;
+    }
+
+    pub fn apply_noise(&mut self) {
+        let Some(noise) = self.noise else { return };
+
+        for sensor in self.sensors.iter_mut() {
+            noise.apply(sensor, &mut self.rng);
+        }
+    }
+
+    pub fn read_inputs(&mut self, road: &Road, speed_ratio: f64) {
+        if self.rig.inputs.is_empty() { return }
+
+        let position = road.locate(&Point::new(self.x, self.y));
+
+        for (value, input) in self.extra_inputs.iter_mut().zip(self.rig.inputs.iter()) {
+            *value = input.read(road, position.as_ref(), self.angle, speed_ratio);
+        }
     }
 
     pub fn reset(&mut self) {
-        self.sensors.iter_mut().for_each(|s| s.reading = 0.0);
+        self.sensors.iter_mut().for_each(|s| s.reading = None);
     }
 
-    pub fn check(&mut self, obstacles: &Vec<Line>) {
-        if obstacles.is_empty() { return }
-        let last = obstacles.last().unwrap();
+    pub fn inputs(&self) -> Vec<f64> {
+        let mut inputs: Vec<f64> = self.sensors.iter().map(Sensor::input).collect();
+
+        if self.rig.kind_inputs {
+            inputs.extend(self.sensors.iter().map(Sensor::kind_input));
+        }
+        inputs.extend(self.extra_inputs.iter().copied());
+
+        inputs
+    }
+
+    pub fn check_grid(&mut self, grid: &SpatialGrid) {
+        let Some(last) = grid.lines.last() else { return };
 
         for sensor in self.sensors.iter_mut() {
-            for obstacle in obstacles.iter() {
+            grid.query_into(&sensor.ray.start, &sensor.ray.end, &mut self.candidates);
+
+            for &index in self.candidates.iter() {
+                let obstacle = &grid.lines[index];
+
                 // skip the last line which is the finish line
                 if obstacle.matches_both_points(last) { continue }
 
-                let intersection = sensor.ray.get_intersection(obstacle);
+                sensor.check_line(obstacle, HitKind::Road);
+            }
+        }
+    }
 
-                if let Some(intersection) = intersection {
-                    if !intersection.intersects { continue }
+    // `body` is the sensing car's own hitbox, cars already overlapping it (like a stack sharing
+    // a spawn point) would read 0 on most rays, so they are ignored as they are for collisions
+    pub fn check_targets(&mut self, targets: &SensorTargets, body: &Polygon) {
+        for (index, hitbox) in targets.cars.iter().enumerate() {
+            if targets.exclude == Some(index) || body.overlaps_convex(hitbox) { continue }
 
-                    if sensor.reading == 0.0 || intersection.offset < sensor.reading {
-                        sensor.reading = intersection.offset;
-                    }
-                }
-            }
+            self.check_polygon(hitbox, HitKind::Car);
         }
+
+        targets.obstacles.iter().for_each(|obstacle| self.check_polygon(obstacle, HitKind::Obstacle));
     }
 
-    pub fn check_polygon(&mut self, obstacle: &Polygon) {
-        self.check(&obstacle.lines());
+    pub fn check_polygon(&mut self, obstacle: &Polygon, kind: HitKind) {
+        let Some((min, max)) = obstacle.bounds() else { return };
+
+        // skip polygons that no ray can reach
+        let reach = self.rig.reach();
+        if max.x < self.x - reach || min.x > self.x + reach || max.y < self.y - reach || min.y > self.y + reach {
+            return;
+        }
+
+        for line in obstacle.lines() {
+            self.sensors.iter_mut().for_each(|sensor| sensor.check_line(&line, kind));
+        }
         // for sensor in self.sensors.iter_mut() {
         //     for line in obstacle.lines() {
         //         let intersection = sensor.ray.get_intersection(&line);
@@ -85,26 +405,11 @@ impl Sensors {
         //     }
         // }
     }
-
-    pub fn translate(&mut self, x: f64, y: f64) {
-        self.x += x;
-        self.y += y;
-        self.sensors.iter_mut().for_each(|s| {
-            s.ray.translate(x, y)
-        })
-    }
-
-    pub fn rotate(&mut self, angle: f64) {
-        self.angle += angle;
-        self.sensors.iter_mut().for_each(|s| {
-            s.ray.rotate(angle)
-        })
-    }
 }
 
 impl Drawable for Sensors {
-    fn draw(&self, context: &web_sys::CanvasRenderingContext2d) {
-        // context.set_stroke_style(&JsValue::from_str("yellow"));
+    fn draw(&self, context: &dyn Renderer) {
+        // context.set_stroke_color("yellow");
         // context.set_line_width(1.0);
     
         // for sensor in self.sensors.iter() {
@@ -118,22 +423,31 @@ impl Drawable for Sensors {
         context.set_line_width(1.0);
 
         for sensor in self.sensors.iter() {
-            context.set_stroke_style(&JsValue::from_str("lime"));
-
-            let reading_point = Point::new(
-                sensor.ray.start.x + (sensor.reading * sensor.length) * sensor.ray.angle().cos(), 
-                sensor.ray.start.y + (sensor.reading * sensor.length) * sensor.ray.angle().sin()
-            ); 
+            let reading_point = sensor.reading.map_or(sensor.ray.end, |r| r.point);
 
+            context.set_stroke_color("lime");
             context.begin_path();
             context.move_to(sensor.ray.start.x, sensor.ray.start.y);
             context.line_to(reading_point.x, reading_point.y); 
             context.stroke();
 
-            context.set_stroke_style(&JsValue::from_str("black"));
-            context.set_fill_style(&JsValue::from_str("lime"));
+            let Some(reading) = sensor.reading else { continue };
+
+            // the part of the ray past the hit is blocked
+            context.set_stroke_color("gray");
+            context.begin_path();
+            context.move_to(reading_point.x, reading_point.y);
+            context.line_to(sensor.ray.end.x, sensor.ray.end.y);
+            context.stroke();
+
+            context.set_stroke_color("black");
+            context.set_fill_color(match reading.kind {
+                HitKind::Road => "lime",
+                HitKind::Obstacle => "magenta",
+                HitKind::Car => "orange",
+            });
             context.begin_path();
-            context.arc(reading_point.x, reading_point.y, 2.5, 0.0, 2.0 * std::f64::consts::PI).unwrap();
+            context.arc(reading_point.x, reading_point.y, 2.5, 0.0, 2.0 * std::f64::consts::PI);
             context.fill();
             context.stroke();
         }
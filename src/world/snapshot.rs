@@ -0,0 +1,20 @@
+use crate::{geo::Point, Renderer, Road, SvgRenderer};
+
+pub const SNAPSHOT_PADDING: f64 = 50.0;
+
+pub fn render_svg(road: &Road, padding: f64, draw: impl FnOnce(&dyn Renderer)) -> String {
+    let (min, max) = road.hitbox.bounds().unwrap_or((Point::default(), Point::new(800.0, 800.0)));
+
+    let renderer = SvgRenderer::new(
+        (max.x - min.x + padding * 2.0).ceil(),
+        (max.y - min.y + padding * 2.0).ceil(),
+    );
+
+    renderer.set_fill_color("#eeeeee");
+    renderer.fill_rect(0.0, 0.0, renderer.width, renderer.height);
+
+    renderer.translate(padding - min.x, padding - min.y);
+    draw(&renderer);
+
+    renderer.finish()
+}